@@ -0,0 +1,62 @@
+//! Running an application in an event loop, see `Terminal::run`.
+
+use std::time::Duration;
+
+use crate::backend::{Backend, DefaultBackend};
+use crate::cli::{Event, Terminal};
+
+/// What `Terminal::run` does after an app has handled an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+  /// Keeps running without drawing, as nothing visible changed.
+  Continue,
+  /// Draws the app again, then keeps running.
+  Redraw,
+  /// Stops the loop.
+  Quit
+}
+
+/// An application driven by `Terminal::run`, which handles events and draws itself to the terminal's layers.
+///
+/// # Examples
+/// A counter which counts up with every tick until a key is pressed:
+/// ```no_run
+/// # use std::time::Duration;
+/// # use utile_cli::app::{App, Control};
+/// # use utile_cli::cli::*;
+/// struct Counter { count: u32 }
+///
+/// impl App for Counter {
+///   fn handle_event(&mut self, event: Event) -> Control {
+///     match event {
+///       Event::Tick => { self.count += 1; Control::Redraw },
+///       Event::Key(_) => Control::Quit,
+///       _ => Control::Continue
+///     }
+///   }
+///
+///   fn draw(&self, t: &mut Terminal) {
+///     t.layer_front_mut().index(0, 0).set_content(self.count.to_string());
+///   }
+///
+///   fn tick_rate(&self) -> Option<Duration> {
+///     Some(Duration::from_millis(100))
+///   }
+/// }
+///
+/// let mut t = Terminal::new();
+/// t.add_layer2d(Layer2D::new(0, 0, 1, 1, Layer::new(0, 0)));
+/// t.run(&mut Counter { count: 0 });
+/// ```
+pub trait App<B: Backend = DefaultBackend> {
+  /// Updates the app for an event, returning whether it needs to be drawn again or should quit.
+  fn handle_event(&mut self, event: Event) -> Control;
+
+  /// Updates the terminal's layers to show the app, which `Terminal::run` then refreshes.
+  fn draw(&self, t: &mut Terminal<B>);
+
+  /// How often `Event::Tick` is sent, `None` (never) by default.
+  fn tick_rate(&self) -> Option<Duration> {
+    None
+  }
+}
@@ -0,0 +1,688 @@
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::VecDeque;
+use std::io::{self, Read, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use unicode_width::UnicodeWidthChar;
+
+use super::Backend;
+use crate::builder::Options;
+use crate::cli::{Event, Modifiers, MouseButton, MouseEvent, MouseKind};
+use crate::error::{Error, Result};
+
+/// A backend that writes ANSI / VT100 escape sequences to any writer and decodes keys from raw bytes of a reader.
+///
+/// It does not need curses, and unless the alternate screen is enabled, output stays in the normal scrollback.
+/// Positions are relative to the line the cursor was on when the backend was created, which should be the start of a line.
+/// Moving below the lowest line reached so far outputs newlines, scrolling the terminal if needed.
+/// Keys are decoded with the key table of the backend's options, see `KeyTable`.
+///
+/// On a terminal the size is queried from the terminal and resize events are sent when it changes (using `SIGWINCH` on unix),
+/// otherwise the size is 80 by 24 until changed with `set_size`.
+/// Likewise `get_event_timeout` only times out on a terminal, other readers are expected to have their input ready.
+///
+/// # Examples
+/// The escape sequences can be inspected by writing to a `Vec<u8>`:
+/// ```
+/// # use utile_cli::backend::{AnsiBackend, Backend};
+/// let b = AnsiBackend::new(Vec::new(), &b"\x1b[A"[..]);
+/// b.out("Hi");
+/// b.move_to(0, 1);
+/// assert_eq!(&b.writer()[..], b"Hi\r\n\x1b[1G");
+/// ```
+pub struct AnsiBackend<W: Write, R: Read> {
+  writer: RefCell<W>,
+  reader: RefCell<R>,
+  pending: RefCell<VecDeque<u8>>,
+  cursor: Cell<(i32, i32)>,
+  lowest: Cell<i32>,
+  size: Cell<(i32, i32)>,
+  resized: Cell<bool>,
+  options: RefCell<Options>,
+  #[cfg(unix)]
+  tty: Option<tty::Tty>
+}
+
+// reports presses, releases and drags in the SGR format, which has no limit on positions
+const MOUSE_ON: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1006h";
+const MOUSE_OFF: &str = "\x1b[?1006l\x1b[?1002l\x1b[?1000l";
+// makes the terminal wrap pasted text in `PASTE_START` and `PASTE_END`
+pub(super) const PASTE_ON: &str = "\x1b[?2004h";
+pub(super) const PASTE_OFF: &str = "\x1b[?2004l";
+pub(super) const PASTE_START: &str = "\x1b[200~";
+pub(super) const PASTE_END: &str = "\x1b[201~";
+
+/// An unbuffered reader over the standard input, used by `AnsiBackend::stdio`.
+///
+/// Unlike `Stdin` it never reads ahead, so the backend can tell whether the rest of an escape sequence has arrived.
+pub struct TtyInput {
+  #[cfg(not(unix))]
+  stdin: io::Stdin
+}
+
+impl<W: Write, R: Read> AnsiBackend<W, R> {
+  /// Returns a backend writing to `writer` and reading keys from `reader`.
+  ///
+  /// The terminal mode is left untouched, see `AnsiBackend::stdio` for a backend that turns off line buffering and echo.
+  pub fn new(writer: W, reader: R) -> AnsiBackend<W, R> {
+    AnsiBackend {
+      writer: RefCell::new(writer),
+      reader: RefCell::new(reader),
+      pending: RefCell::new(VecDeque::new()),
+      cursor: Cell::new((0, 0)),
+      lowest: Cell::new(0),
+      size: Cell::new((80, 24)),
+      resized: Cell::new(false),
+      // bracketed paste is off until configured
+      options: RefCell::new(Options { bracketed_paste: false, ..Options::default() }),
+      #[cfg(unix)]
+      tty: None
+    }
+  }
+
+  /// Returns the writer the escape sequences are written to.
+  pub fn writer(&self) -> Ref<'_, W> {
+    self.writer.borrow()
+  }
+
+  /// Sets the size of the screen, queuing a resize event.
+  ///
+  /// Useful when the writer is not a terminal, or the size is known some other way.
+  pub fn set_size(&self, width: i32, height: i32) {
+    self.size.set((width, height));
+    self.resized.set(true);
+  }
+
+  // errors are ignored like curses' return codes are
+  fn write(&self, s: &str) {
+    let _ = self.writer.borrow_mut().write_all(s.as_bytes());
+  }
+
+  // reads a byte, `Err` if the read was interrupted by a signal such as a resize
+  fn try_read_byte(&self) -> io::Result<Option<u8>> {
+    if let Some(b) = self.pending.borrow_mut().pop_front() {
+      return Ok(Some(b));
+    }
+    let mut buf = [0; 1];
+    match self.reader.borrow_mut().read(&mut buf) {
+      Ok(1) => Ok(Some(buf[0])),
+      Err(e) if e.kind() == io::ErrorKind::Interrupted => Err(e),
+      _ => Ok(None)
+    }
+  }
+
+  // reads a byte, retrying interrupted reads in the middle of a key
+  fn read_byte(&self) -> Option<u8> {
+    loop {
+      if let Ok(b) = self.try_read_byte() {
+        return b;
+      }
+    }
+  }
+
+  // returns true once for every resize since the last call
+  fn take_resize(&self) -> bool {
+    #[cfg(unix)]
+    if self.tty.is_some() && tty::take_resize() {
+      return true;
+    }
+    self.resized.replace(false)
+  }
+
+  // waits up to the escape delay for more input after an escape, other readers are expected to have it ready
+  fn escape_follows(&self) -> bool {
+    if !self.pending.borrow().is_empty() {
+      return true;
+    }
+    #[cfg(unix)]
+    if let Some(tty) = &self.tty {
+      return tty.poll(self.options.borrow().esc_delay);
+    }
+    true
+  }
+
+  // returns false if no input arrives before `deadline` or a signal interrupts the wait
+  #[cfg_attr(not(unix), allow(unused_variables))]
+  fn input_ready(&self, deadline: Instant) -> bool {
+    if !self.pending.borrow().is_empty() {
+      return true;
+    }
+    #[cfg(unix)]
+    if let Some(tty) = &self.tty {
+      return tty.poll(deadline.saturating_duration_since(Instant::now()));
+    }
+    true
+  }
+
+  // returns true if reads from the tty return straight away, see `Options::nodelay`
+  fn nodelay(&self) -> bool {
+    #[cfg(unix)]
+    if self.tty.is_some() {
+      return self.options.borrow().nodelay;
+    }
+    false
+  }
+
+  // reads an event, waiting until `deadline` for it to start if there is one
+  fn read_event(&self, deadline: Option<Instant>) -> Result<Option<Event>> {
+    // prompts draw without refreshing, so what was typed so far is shown before waiting for more
+    let _ = self.writer.borrow_mut().flush();
+    loop {
+      if self.take_resize() {
+        let (width, height) = self.size();
+        return Ok(Some(Event::Resize(width, height)));
+      }
+      if let Some(deadline) = deadline {
+        if !self.input_ready(deadline) {
+          if Instant::now() >= deadline {
+            return Ok(None);
+          }
+          continue;
+        }
+      }
+      let b = match self.try_read_byte() {
+        Ok(Some(b)) => b,
+        // a non-blocking read returns nothing when no key was pressed yet, while at the end of input the tty still polls ready
+        Ok(None) if self.nodelay() && !self.input_ready(Instant::now()) => return Ok(None),
+        Ok(None) => return Err(Error::InputEnded),
+        Err(_) => continue
+      };
+      // unknown sequences are skipped, only the end of input is an error
+      let seq = self.read_sequence(b).ok_or(Error::InputEnded)?;
+      if seq == b"\x1b[M" {
+        // legacy mouse reports are followed by three bytes which are not part of the sequence
+        let mut bytes = [0; 3];
+        for b in bytes.iter_mut() {
+          *b = self.read_byte().ok_or(Error::InputEnded)?;
+        }
+        if let Some(m) = decode_x10_mouse(bytes) {
+          return Ok(Some(Event::Mouse(m)));
+        }
+        continue;
+      }
+      if seq == PASTE_START.as_bytes() && self.options.borrow().bracketed_paste {
+        return Ok(Some(Event::Paste(self.read_paste())));
+      }
+      if seq.starts_with(b"\x1b[<") {
+        if let Some(m) = decode_sgr_mouse(&seq[3..]) {
+          return Ok(Some(Event::Mouse(m)));
+        }
+        continue;
+      }
+      if let Some(key) = self.options.borrow().key_table.decode(&seq) {
+        return Ok(Some(Event::Key(key)));
+      }
+    }
+  }
+
+  // reads pasted text up to the end of the paste or of input
+  fn read_paste(&self) -> String {
+    let mut bytes = Vec::new();
+    while !bytes.ends_with(PASTE_END.as_bytes()) {
+      match self.read_byte() {
+        Some(b) => bytes.push(b),
+        None => break
+      }
+    }
+    if bytes.ends_with(PASTE_END.as_bytes()) {
+      bytes.truncate(bytes.len() - PASTE_END.len());
+    }
+    normalize_paste(&String::from_utf8_lossy(&bytes))
+  }
+
+  // reads the rest of the key starting with byte `first`, such as an escape sequence or a utf-8 character
+  fn read_sequence(&self, first: u8) -> Option<Vec<u8>> {
+    let mut seq = vec![first];
+    if first != 0x1b {
+      self.read_utf8(&mut seq)?;
+      return Some(seq);
+    }
+    // a lone escape is the Escape key
+    if !self.escape_follows() {
+      return Some(seq);
+    }
+    let b = match self.read_byte() {
+      Some(b) => b,
+      None => return Some(seq)
+    };
+    seq.push(b);
+    match b {
+      b'[' => {
+        // the linux console's function keys, such as `ESC [ [ A`
+        if let Some(b'[') = self.peek_byte() {
+          seq.push(self.read_byte()?);
+          seq.push(self.read_byte()?);
+          return Some(seq);
+        }
+        loop {
+          let b = self.read_byte()?;
+          seq.push(b);
+          if (0x40..=0x7e).contains(&b) {
+            break;
+          }
+        }
+      },
+      b'O' => seq.push(self.read_byte()?),
+      0x1b => (),
+      _ => {
+        // escape followed by another key is that key with Alt held
+        let mut rest = vec![b];
+        self.read_utf8(&mut rest)?;
+        seq.extend(&rest[1..]);
+      }
+    }
+    Some(seq)
+  }
+
+  // reads the continuation bytes of a utf-8 character started in `bytes`
+  fn read_utf8(&self, bytes: &mut Vec<u8>) -> Option<()> {
+    let len = match bytes[0] {
+      0x00..=0x7f => 1,
+      0xc0..=0xdf => 2,
+      0xe0..=0xef => 3,
+      0xf0..=0xf7 => 4,
+      // not a character, left for the key table to skip
+      _ => 1
+    };
+    for _ in 1..len {
+      bytes.push(self.read_byte()?);
+    }
+    Some(())
+  }
+
+  fn peek_byte(&self) -> Option<u8> {
+    let b = self.read_byte()?;
+    self.pending.borrow_mut().push_front(b);
+    Some(b)
+  }
+
+  // switches to the alternate screen, where positions start from the top left corner again
+  fn enter_alternate_screen(&self) {
+    self.write("\x1b[?1049h\x1b[H");
+    self.cursor.set((0, 0));
+    self.lowest.set(0);
+  }
+}
+
+impl AnsiBackend<Stdout, TtyInput> {
+  /// Returns a backend over the standard output and input.
+  ///
+  /// On unix, the terminal is switched to the input mode of the backend's options (cbreak by default) until the backend is dropped.
+  pub fn stdio() -> AnsiBackend<Stdout, TtyInput> {
+    #[allow(unused_mut)]
+    let mut b = AnsiBackend::new(io::stdout(), TtyInput::new());
+    #[cfg(unix)]
+    {
+      b.tty = tty::Tty::open(libc::STDIN_FILENO).ok();
+      if let Some(tty) = &b.tty {
+        tty.set_mode(&b.options.borrow());
+      }
+    }
+    b
+  }
+}
+
+impl Default for AnsiBackend<Stdout, TtyInput> {
+  fn default() -> AnsiBackend<Stdout, TtyInput> {
+    AnsiBackend::stdio()
+  }
+}
+
+impl<W: Write, R: Read> Backend for AnsiBackend<W, R> {
+  fn out(&self, s: &str) {
+    let (mut x, mut y) = self.cursor.get();
+    let mut buf = String::new();
+    for c in s.chars() {
+      if c == '\n' {
+        // like curses, a newline clears the rest of the line
+        buf.push_str("\x1b[K\r\n");
+        x = 0;
+        y += 1;
+      } else {
+        buf.push(c);
+        x += c.width().unwrap_or(0) as i32;
+      }
+    }
+    self.write(&buf);
+    self.cursor.set((x, y));
+    if y > self.lowest.get() {
+      self.lowest.set(y);
+    }
+  }
+
+  fn out_dim(&self, s: &str) {
+    self.write("\x1b[2m");
+    self.out(s);
+    self.write("\x1b[22m");
+  }
+
+  fn move_to(&self, x: i32, y: i32) {
+    if x < 0 || y < 0 || (x, y) == self.cursor.get() {
+      return;
+    }
+    let (_, cy) = self.cursor.get();
+    let lowest = self.lowest.get();
+    let mut buf = String::new();
+    if y < cy {
+      buf.push_str(&format!("\x1b[{}A", cy - y));
+    } else if y > cy {
+      if cy < lowest {
+        buf.push_str(&format!("\x1b[{}B", y.min(lowest) - cy));
+      }
+      if y > lowest {
+        buf.push_str(&"\r\n".repeat((y - lowest) as usize));
+        self.lowest.set(y);
+      }
+    }
+    buf.push_str(&format!("\x1b[{}G", x + 1));
+    self.write(&buf);
+    self.cursor.set((x, y));
+  }
+
+  fn posxy(&self) -> (i32, i32) {
+    self.cursor.get()
+  }
+
+  fn delete(&self) {
+    self.write("\x1b[P");
+  }
+
+  fn refresh(&self) {
+    let _ = self.writer.borrow_mut().flush();
+  }
+
+  fn size(&self) -> (i32, i32) {
+    #[cfg(unix)]
+    if let Some(size) = self.tty.as_ref().and_then(|tty| tty.size()) {
+      return size;
+    }
+    self.size.get()
+  }
+
+  fn top_row(&self) -> i32 {
+    // the lowest line reached is on the screen, at the bottom once the output scrolled
+    (self.lowest.get() - self.size().1 + 1).max(0)
+  }
+
+  fn get_event(&self) -> Option<Event> {
+    self.read_event(None).ok().flatten()
+  }
+
+  fn get_event_timeout(&self, timeout: Duration) -> Result<Option<Event>> {
+    self.read_event(Some(Instant::now() + timeout))
+  }
+
+  fn configure(&self, options: &Options) {
+    let old = self.options.replace(options.clone());
+    #[cfg(unix)]
+    if let Some(tty) = &self.tty {
+      tty.set_mode(options);
+    }
+    if options.alternate_screen && !old.alternate_screen {
+      self.enter_alternate_screen();
+    } else if !options.alternate_screen && old.alternate_screen {
+      self.write("\x1b[?1049l");
+    }
+    if options.cursor_visible != old.cursor_visible {
+      self.write(if options.cursor_visible { "\x1b[?25h" } else { "\x1b[?25l" });
+    }
+    if options.mouse != old.mouse {
+      self.write(if options.mouse { MOUSE_ON } else { MOUSE_OFF });
+    }
+    if options.bracketed_paste != old.bracketed_paste {
+      self.write(if options.bracketed_paste { PASTE_ON } else { PASTE_OFF });
+    }
+    self.refresh();
+  }
+
+  fn suspend(&self) {
+    let options = self.options.borrow();
+    if !options.cursor_visible {
+      self.write("\x1b[?25h");
+    }
+    if options.alternate_screen {
+      self.write("\x1b[?1049l");
+    }
+    if options.mouse {
+      self.write(MOUSE_OFF);
+    }
+    if options.bracketed_paste {
+      self.write(PASTE_OFF);
+    }
+    self.refresh();
+    #[cfg(unix)]
+    if let Some(tty) = &self.tty {
+      tty.restore();
+    }
+  }
+
+  fn resume(&self) {
+    #[cfg(unix)]
+    if let Some(tty) = &self.tty {
+      tty.apply();
+    }
+    let options = self.options.borrow();
+    if options.alternate_screen {
+      self.enter_alternate_screen();
+    }
+    if !options.cursor_visible {
+      self.write("\x1b[?25l");
+    }
+    if options.mouse {
+      self.write(MOUSE_ON);
+    }
+    if options.bracketed_paste {
+      self.write(PASTE_ON);
+    }
+    self.refresh();
+  }
+
+  fn restorer(&self) -> Option<Box<dyn Fn() + Send + Sync>> {
+    #[cfg(unix)]
+    if let Some(tty) = &self.tty {
+      let options = self.options.borrow();
+      let mut reset = String::new();
+      if !options.cursor_visible {
+        reset.push_str("\x1b[?25h");
+      }
+      if options.alternate_screen {
+        reset.push_str("\x1b[?1049l");
+      }
+      if options.mouse {
+        reset.push_str(MOUSE_OFF);
+      }
+      if options.bracketed_paste {
+        reset.push_str(PASTE_OFF);
+      }
+      let restore = tty.restorer();
+      return Some(Box::new(move || {
+        let mut out = io::stdout();
+        let _ = out.write_all(reset.as_bytes());
+        let _ = out.flush();
+        restore();
+      }));
+    }
+    None
+  }
+}
+
+// terminals send newlines in pastes as Enter does, usually '\r'
+pub(super) fn normalize_paste(text: &str) -> String {
+  text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+// decodes the button code of a mouse report, `release` being true for the release of an unknown button
+fn decode_mouse(code: u32, x: i32, y: i32, release: bool) -> Option<MouseEvent> {
+  let button = match code & 3 {
+    0 => MouseButton::Left,
+    1 => MouseButton::Middle,
+    2 => MouseButton::Right,
+    _ => MouseButton::Left
+  };
+  let kind = match code & !(4 | 8 | 16) {
+    64 => MouseKind::ScrollUp,
+    65 => MouseKind::ScrollDown,
+    c if c & 64 != 0 => return None,
+    c if c & 32 != 0 => match c & 3 {
+      3 => return None,
+      _ => MouseKind::Drag(button)
+    },
+    c if release || c & 3 == 3 => MouseKind::Release(button),
+    _ => MouseKind::Press(button)
+  };
+  let mods = Modifiers { shift: code & 4 != 0, alt: code & 8 != 0, ctrl: code & 16 != 0 };
+  Some(MouseEvent { kind, x, y, mods })
+}
+
+// decodes an SGR mouse report after `ESC [ <`, such as `0;12;3M`, whose positions start from 1
+fn decode_sgr_mouse(report: &[u8]) -> Option<MouseEvent> {
+  let (last, params) = report.split_last()?;
+  let mut params = std::str::from_utf8(params).ok()?.split(';').map(|p| p.parse::<u32>().ok());
+  let (code, x, y) = (params.next()??, params.next()??, params.next()??);
+  match last {
+    b'M' => decode_mouse(code, x as i32 - 1, y as i32 - 1, false),
+    b'm' => decode_mouse(code, x as i32 - 1, y as i32 - 1, true),
+    _ => None
+  }
+}
+
+// decodes a legacy mouse report after `ESC [ M`, every byte being offset by 32 and positions starting from 1
+fn decode_x10_mouse(bytes: [u8; 3]) -> Option<MouseEvent> {
+  let code = bytes[0].checked_sub(32)? as u32;
+  decode_mouse(code, bytes[1].checked_sub(33)? as i32, bytes[2].checked_sub(33)? as i32, false)
+}
+
+impl TtyInput {
+  /// Returns a reader over the standard input.
+  pub fn new() -> TtyInput {
+    TtyInput {
+      #[cfg(not(unix))]
+      stdin: io::stdin()
+    }
+  }
+}
+
+impl Default for TtyInput {
+  fn default() -> TtyInput {
+    TtyInput::new()
+  }
+}
+
+impl Read for TtyInput {
+  #[cfg(unix)]
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let n = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+    if n < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(n as usize)
+  }
+
+  #[cfg(not(unix))]
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    self.stdin.read(buf)
+  }
+}
+
+#[cfg(unix)]
+mod tty {
+  use std::cell::Cell;
+  use std::io;
+  use std::mem::MaybeUninit;
+  use std::sync::atomic::{AtomicBool, Ordering};
+  use std::time::Duration;
+
+  use crate::builder::{InputMode, Options};
+
+  static RESIZED: AtomicBool = AtomicBool::new(false);
+
+  extern "C" fn on_resize(_: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+  }
+
+  // returns true once for every resize since the last call
+  pub fn take_resize() -> bool {
+    RESIZED.swap(false, Ordering::SeqCst)
+  }
+
+  // a terminal device whose original attributes and resize handler are restored on drop
+  pub struct Tty {
+    fd: libc::c_int,
+    original: libc::termios,
+    mode: Cell<libc::termios>,
+    winch: libc::sigaction
+  }
+
+  impl Tty {
+    pub fn open(fd: libc::c_int) -> io::Result<Tty> {
+      let mut t = MaybeUninit::uninit();
+      if unsafe { libc::tcgetattr(fd, t.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+      }
+      let original = unsafe { t.assume_init() };
+      // without SA_RESTART, so a blocking read returns when the terminal is resized
+      let winch = unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        let mut old = std::mem::zeroed();
+        libc::sigaction(libc::SIGWINCH, &action, &mut old);
+        old
+      };
+      Ok(Tty { fd, original, mode: Cell::new(original), winch })
+    }
+
+    // the size of the terminal in the form (width, height), `None` if it is unknown
+    pub fn size(&self) -> Option<(i32, i32)> {
+      let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+      if unsafe { libc::ioctl(self.fd, libc::TIOCGWINSZ, &mut size) } != 0 || size.ws_col == 0 {
+        return None;
+      }
+      Some((size.ws_col as i32, size.ws_row as i32))
+    }
+
+    // echo is always turned off, as `Terminal` echoes keys itself
+    pub fn set_mode(&self, options: &Options) {
+      let mut mode = self.original;
+      mode.c_lflag &= !libc::ECHO;
+      match options.input_mode {
+        InputMode::Cooked => (),
+        InputMode::Cbreak => { mode.c_lflag &= !libc::ICANON; },
+        InputMode::Raw => {
+          mode.c_lflag &= !(libc::ICANON | libc::ISIG | libc::IEXTEN);
+          mode.c_iflag &= !(libc::IXON | libc::ICRNL);
+        }
+      }
+      mode.c_cc[libc::VMIN] = if options.nodelay { 0 } else { 1 };
+      mode.c_cc[libc::VTIME] = 0;
+      self.mode.set(mode);
+      self.apply();
+    }
+
+    pub fn apply(&self) {
+      unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.mode.get()); }
+    }
+
+    pub fn restore(&self) {
+      unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.original); }
+    }
+
+    pub fn restorer(&self) -> Box<dyn Fn() + Send + Sync> {
+      let (fd, original) = (self.fd, self.original);
+      Box::new(move || unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original); })
+    }
+
+    // returns true if input is ready within `timeout`
+    pub fn poll(&self, timeout: Duration) -> bool {
+      let mut fds = libc::pollfd { fd: self.fd, events: libc::POLLIN, revents: 0 };
+      unsafe { libc::poll(&mut fds, 1, timeout.as_millis().min(i32::MAX as u128) as libc::c_int) > 0 }
+    }
+  }
+
+  impl Drop for Tty {
+    fn drop(&mut self) {
+      self.restore();
+      unsafe { libc::sigaction(libc::SIGWINCH, &self.winch, std::ptr::null_mut()); }
+    }
+  }
+}
@@ -0,0 +1,284 @@
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use pancurses::{Window, Input, cbreak, curs_set, endwin, getmouse, initscr, mmask_t, mouseinterval, mousemask, nocbreak, noecho, noraw, raw};
+use pancurses::{A_DIM, ALL_MOUSE_EVENTS, BUTTON1_PRESSED, BUTTON1_RELEASED, BUTTON2_PRESSED, BUTTON2_RELEASED, BUTTON3_PRESSED, BUTTON3_RELEASED};
+use pancurses::{BUTTON4_PRESSED, BUTTON5_PRESSED, BUTTON_ALT, BUTTON_CTRL, BUTTON_SHIFT, REPORT_MOUSE_POSITION};
+
+use super::Backend;
+use super::ansi::{PASTE_END, PASTE_OFF, PASTE_ON, PASTE_START, normalize_paste};
+use crate::builder::{InputMode, Options};
+use crate::cli::{Event, Key, Modifiers, MouseButton, MouseEvent, MouseKind};
+use crate::error::Result;
+use crate::keytable::KeyTable;
+
+/// A backend that draws to a pancurses window.
+///
+/// This is the default backend used by `Terminal::new`.
+pub struct CursesBackend {
+  win: Window,
+  keys: RefCell<KeyTable>,
+  // the button being dragged, as curses only reports the position
+  held: Cell<Option<MouseButton>>,
+  // restored after reading with a timeout
+  nodelay: Cell<bool>,
+  // how long to wait after Escape for a key pressed with Alt
+  esc_delay: Cell<Duration>,
+  bracketed_paste: Cell<bool>
+}
+
+impl CursesBackend {
+  /// Initializes curses and returns a backend over the standard screen.
+  /// 
+  /// Curses echoing is turned off, as `Terminal` echoes keys itself.
+  pub fn new() -> CursesBackend {
+    let win = initscr();
+    win.keypad(true);
+    noecho();
+    CursesBackend::from_window(win)
+  }
+
+  /// Returns a backend over an already initialized window.
+  /// 
+  /// `noecho` should have been called, otherwise hidden input will be echoed by curses.
+  pub fn from_window(win: Window) -> CursesBackend {
+    CursesBackend {
+      win,
+      keys: RefCell::new(KeyTable::default()),
+      held: Cell::new(None),
+      nodelay: Cell::new(false),
+      esc_delay: Cell::new(Options::default().esc_delay),
+      bracketed_paste: Cell::new(false)
+    }
+  }
+
+  /// Returns the underlying pancurses window.
+  pub fn window(&self) -> &Window {
+    &self.win
+  }
+
+  // the event for curses input, `None` for input without a `Key` or `MouseKind`
+  fn decode(&self, input: Input) -> Option<Event> {
+    match input {
+      // curses has already resized the window by the time it returns this
+      Input::KeyResize => {
+        let (width, height) = self.size();
+        Some(Event::Resize(width, height))
+      },
+      Input::KeyMouse => self.mouse_event().map(Event::Mouse),
+      Input::Character('\x1b') if self.bracketed_paste.get() && self.paste_follows() => Some(Event::Paste(self.read_paste())),
+      Input::Character('\x1b') => self.escaped().map(Event::Key),
+      input => self.keys.borrow().decode_curses(input).map(Event::Key)
+    }
+  }
+
+  // terminals send a key pressed with Alt as Escape followed by the key, which curses returns one at a time,
+  // so the next input is read within the escape delay to decode them together
+  fn escaped(&self) -> Option<Key> {
+    let escape = Input::Character('\x1b');
+    self.win.timeout(self.esc_delay.get().as_millis().min(i32::MAX as u128) as i32);
+    let next = self.win.getch();
+    self.win.nodelay(self.nodelay.get());
+    let keys = self.keys.borrow();
+    if let Some(next) = next {
+      if let Some(key) = keys.decode_curses_seq(&[escape, next]) {
+        return Some(key);
+      }
+      self.win.ungetch(&next);
+    }
+    keys.decode_curses(escape)
+  }
+
+  // curses does not know the paste sequences, so they are read a character at a time after the escape
+  fn paste_follows(&self) -> bool {
+    let mut read = vec![];
+    let mut matched = true;
+    self.win.timeout(0);
+    for c in PASTE_START.chars().skip(1) {
+      let input = self.win.getch();
+      matched = input == Some(Input::Character(c));
+      read.extend(input);
+      if !matched {
+        break;
+      }
+    }
+    self.win.nodelay(self.nodelay.get());
+    if matched {
+      return true;
+    }
+    // put back whatever followed the escape, last first
+    for input in read.iter().rev() {
+      self.win.ungetch(input);
+    }
+    false
+  }
+
+  // reads pasted text up to the end of the paste or of input
+  fn read_paste(&self) -> String {
+    let mut text = String::new();
+    self.win.nodelay(false);
+    while !text.ends_with(PASTE_END) {
+      match self.win.getch() {
+        Some(Input::Character(c)) => text.push(c),
+        Some(Input::KeyEnter) => text.push('\n'),
+        Some(_) => (),
+        None => break
+      }
+    }
+    self.win.nodelay(self.nodelay.get());
+    if text.ends_with(PASTE_END) {
+      text.truncate(text.len() - PASTE_END.len());
+    }
+    normalize_paste(&text)
+  }
+
+  // turns bracketed paste on or off, which curses has no function for
+  fn write_paste_mode(&self, on: bool) {
+    let mut out = std::io::stdout();
+    let _ = out.write_all(if on { PASTE_ON } else { PASTE_OFF }.as_bytes());
+    let _ = out.flush();
+  }
+
+  // reads the mouse event curses returned `KeyMouse` for, `None` if it has no `MouseKind`
+  fn mouse_event(&self) -> Option<MouseEvent> {
+    let m = getmouse().ok()?;
+    let state = m.bstate as mmask_t;
+    let pressed = [(BUTTON1_PRESSED, MouseButton::Left), (BUTTON2_PRESSED, MouseButton::Middle), (BUTTON3_PRESSED, MouseButton::Right)];
+    let released = [(BUTTON1_RELEASED, MouseButton::Left), (BUTTON2_RELEASED, MouseButton::Middle), (BUTTON3_RELEASED, MouseButton::Right)];
+    let kind = if state & BUTTON4_PRESSED != 0 {
+      MouseKind::ScrollUp
+    } else if state & BUTTON5_PRESSED != 0 {
+      MouseKind::ScrollDown
+    } else if let Some(&(_, b)) = pressed.iter().find(|(mask, _)| state & mask != 0) {
+      self.held.set(Some(b));
+      MouseKind::Press(b)
+    } else if let Some(&(_, b)) = released.iter().find(|(mask, _)| state & mask != 0) {
+      self.held.set(None);
+      MouseKind::Release(b)
+    } else if state & REPORT_MOUSE_POSITION != 0 {
+      MouseKind::Drag(self.held.get()?)
+    } else {
+      return None;
+    };
+    let mods = Modifiers { ctrl: state & BUTTON_CTRL != 0, alt: state & BUTTON_ALT != 0, shift: state & BUTTON_SHIFT != 0 };
+    Some(MouseEvent { kind, x: m.x, y: m.y, mods })
+  }
+}
+
+impl Default for CursesBackend {
+  fn default() -> CursesBackend {
+    CursesBackend::new()
+  }
+}
+
+impl Backend for CursesBackend {
+  fn out(&self, s: &str) {
+    self.win.printw(s);
+  }
+
+  fn out_dim(&self, s: &str) {
+    self.win.attron(A_DIM);
+    self.win.printw(s);
+    self.win.attroff(A_DIM);
+  }
+
+  fn move_to(&self, x: i32, y: i32) {
+    self.win.mv(y, x);
+  }
+
+  fn posxy(&self) -> (i32, i32) {
+    (self.win.get_cur_x(), self.win.get_cur_y())
+  }
+
+  fn delete(&self) {
+    self.win.delch();
+  }
+
+  fn refresh(&self) {
+    self.win.refresh();
+  }
+
+  fn size(&self) -> (i32, i32) {
+    (self.win.get_max_x(), self.win.get_max_y())
+  }
+
+  fn get_event(&self) -> Option<Event> {
+    loop {
+      // input without an event is skipped, only the end of input returns `None`
+      if let Some(event) = self.decode(self.win.getch()?) {
+        return Some(event);
+      }
+    }
+  }
+
+  // curses cannot tell a timeout apart from other errors, so this never returns `Error::InputEnded`
+  fn get_event_timeout(&self, timeout: Duration) -> Result<Option<Event>> {
+    let deadline = Instant::now() + timeout;
+    let event = loop {
+      let left = deadline.saturating_duration_since(Instant::now());
+      self.win.timeout(left.as_millis().min(i32::MAX as u128) as i32);
+      match self.win.getch() {
+        Some(input) => if let Some(event) = self.decode(input) {
+          break Some(event);
+        },
+        None => break None
+      }
+    };
+    // back to blocking, or not waiting at all
+    self.win.nodelay(self.nodelay.get());
+    Ok(event)
+  }
+
+  fn configure(&self, options: &Options) {
+    self.keys.replace(options.key_table.clone());
+    match options.input_mode {
+      InputMode::Cooked => { noraw(); nocbreak(); },
+      InputMode::Cbreak => { noraw(); cbreak(); },
+      InputMode::Raw => { raw(); }
+    }
+    curs_set(if options.cursor_visible { 1 } else { 0 });
+    self.win.nodelay(options.nodelay);
+    self.nodelay.set(options.nodelay);
+    if options.bracketed_paste != self.bracketed_paste.get() {
+      self.write_paste_mode(options.bracketed_paste);
+      self.bracketed_paste.set(options.bracketed_paste);
+    }
+    if options.mouse {
+      // presses and releases are reported as they happen instead of being combined into clicks
+      mouseinterval(0);
+      mousemask(ALL_MOUSE_EVENTS | REPORT_MOUSE_POSITION, std::ptr::null_mut());
+    } else {
+      mousemask(0, std::ptr::null_mut());
+    }
+    self.esc_delay.set(options.esc_delay);
+    #[cfg(unix)]
+    ncurses::set_escdelay(options.esc_delay.as_millis().min(i32::MAX as u128) as i32);
+  }
+
+  fn suspend(&self) {
+    if self.bracketed_paste.get() {
+      self.write_paste_mode(false);
+    }
+    endwin();
+  }
+
+  fn resume(&self) {
+    self.win.refresh();
+    if self.bracketed_paste.get() {
+      self.write_paste_mode(true);
+    }
+  }
+
+  fn restorer(&self) -> Option<Box<dyn Fn() + Send + Sync>> {
+    let paste = self.bracketed_paste.get();
+    Some(Box::new(move || {
+      endwin();
+      if paste {
+        let mut out = std::io::stdout();
+        let _ = out.write_all(PASTE_OFF.as_bytes());
+        let _ = out.flush();
+      }
+    }))
+  }
+}
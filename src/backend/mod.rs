@@ -0,0 +1,106 @@
+//! Backends which a `Terminal` outputs to and reads keys and other events from.
+//!
+//! A `Terminal` does not talk to the console directly, instead it is generic over a `Backend`.
+//! The default backend is `CursesBackend` which wraps a pancurses window.
+//! `AnsiBackend` writes escape sequences to any writer and does not need curses,
+//! and `VirtualBackend` keeps the screen in memory instead, which is useful for testing.
+
+mod ansi;
+#[cfg(feature = "curses")]
+mod curses;
+mod virt;
+
+pub use self::ansi::{AnsiBackend, TtyInput};
+#[cfg(feature = "curses")]
+pub use self::curses::CursesBackend;
+pub use self::virt::VirtualBackend;
+
+/// The backend used by `Terminal::new`.
+/// 
+/// This is `CursesBackend` with the `curses` feature enabled (the default), otherwise `AnsiBackend` over the standard streams.
+#[cfg(feature = "curses")]
+pub type DefaultBackend = CursesBackend;
+
+/// The backend used by `Terminal::new`.
+/// 
+/// This is `CursesBackend` with the `curses` feature enabled (the default), otherwise `AnsiBackend` over the standard streams.
+#[cfg(not(feature = "curses"))]
+pub type DefaultBackend = AnsiBackend<std::io::Stdout, TtyInput>;
+
+use std::time::Duration;
+
+use crate::builder::Options;
+use crate::cli::Event;
+use crate::error::{Error, Result};
+
+/// A target that a `Terminal` can draw to and read keys and other events from.
+///
+/// Like pancurses' `Window`, every method takes `&self`, so a backend that needs to change its own state should use interior mutability (`Cell`, `RefCell`).
+///
+/// # Examples
+/// Creating a terminal from a backend:
+/// ```no_run
+/// # use utile_cli::backend::AnsiBackend;
+/// # use utile_cli::cli::Terminal;
+/// let t = Terminal::with_backend(AnsiBackend::stdio());
+/// ```
+pub trait Backend {
+  /// Outputs a string over the cursor, leaving the cursor after the last character.
+  fn out(&self, s: &str);
+
+  /// Outputs a string like `out`, however dimmed, for hints such as the placeholder of a prompt.
+  /// 
+  /// The default outputs it like any other string, for backends without text attributes.
+  fn out_dim(&self, s: &str) {
+    self.out(s);
+  }
+
+  /// Moves the cursor to position x and y.
+  fn move_to(&self, x: i32, y: i32);
+
+  /// Returns a tuple containing the current cursor position in the form (x, y)
+  fn posxy(&self) -> (i32, i32);
+
+  /// Deletes the character that the cursor is on, shifting the rest of the line left.
+  fn delete(&self);
+
+  /// Pushes any pending output to the screen.
+  fn refresh(&self);
+
+  /// Returns the size of the screen in the form (width, height)
+  fn size(&self) -> (i32, i32);
+
+  /// Returns the row shown at the top of the screen.
+  /// 
+  /// The default is 0, for backends whose rows do not move as output scrolls.
+  fn top_row(&self) -> i32 {
+    0
+  }
+
+  /// Waits for a key or another event such as a resize without echoing it, returning `None` if no event could be read.
+  fn get_event(&self) -> Option<Event>;
+
+  /// Waits up to `timeout` for an event like `get_event`, returning `Ok(None)` if none arrived in time,
+  /// or `Error::InputEnded` if no event could be read.
+  /// 
+  /// The default waits without a timeout, for backends that cannot time out.
+  fn get_event_timeout(&self, _timeout: Duration) -> Result<Option<Event>> {
+    self.get_event().map(Some).ok_or(Error::InputEnded)
+  }
+
+  /// Applies the startup options of a `TerminalBuilder`, ignoring any the backend does not support.
+  /// 
+  /// `Options::echo` is handled by the `Terminal` and does not need to be applied.
+  fn configure(&self, _options: &Options) {}
+
+  /// Restores the terminal to the state it was in before the backend was created, for example to exit or to run another program.
+  fn suspend(&self) {}
+
+  /// Returns to the backend's own mode after `suspend`.
+  fn resume(&self) {}
+
+  /// Returns a function that restores the terminal like `suspend` and can be called from a panic hook, see `Terminal::restore_on_panic`.
+  fn restorer(&self) -> Option<Box<dyn Fn() + Send + Sync>> {
+    None
+  }
+}
@@ -0,0 +1,291 @@
+use std::cell::{Cell, RefCell};
+use std::path::Path;
+use std::time::Duration;
+
+use super::Backend;
+use crate::builder::Options;
+use crate::cli::{Event, Key};
+use crate::input::{InputSource, ScriptedInput};
+use crate::error::Result;
+use crate::snapshot;
+use crate::width;
+
+/// A headless backend that draws to a grid of cells kept in memory.
+///
+/// Output behaves like a curses window without scrolling: text wraps at the right edge, a newline clears the rest of the line, and moves outside of the screen are ignored.
+/// Each cell holds a grapheme cluster, with double width clusters taking two cells like on a terminal.
+/// Keys are read from a `ScriptedInput` which can be fed with `feed`, `feed_script` or `feed_event`, and `resize` queues a resize event.
+/// Once the queue is empty `get_event` returns `None`, while `get_event_timeout` times out straight away, as more keys may be fed later.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::backend::VirtualBackend;
+/// # use utile_cli::cli::*;
+/// let t = Terminal::with_backend(VirtualBackend::new(20, 5));
+/// t.outln("Hello world!".into());
+/// assert_eq!(t.backend().line(0), "Hello world!");
+/// ```
+pub struct VirtualBackend {
+  width: Cell<usize>,
+  height: Cell<usize>,
+  cells: RefCell<Vec<String>>,
+  cursor: Cell<(i32, i32)>,
+  keys: RefCell<ScriptedInput>,
+  suspended: Cell<bool>,
+  options: RefCell<Options>
+}
+
+impl VirtualBackend {
+  /// Returns a blank screen of `width` by `height` cells.
+  pub fn new(width: usize, height: usize) -> VirtualBackend {
+    VirtualBackend {
+      width: Cell::new(width),
+      height: Cell::new(height),
+      cells: RefCell::new(vec![" ".to_string(); width * height]),
+      cursor: Cell::new((0, 0)),
+      keys: RefCell::new(ScriptedInput::default()),
+      suspended: Cell::new(false),
+      options: RefCell::new(Options::default())
+    }
+  }
+
+  /// Queues keys to be returned by `get_event`, in order.
+  pub fn feed<I: IntoIterator<Item = Key>>(&self, keys: I) {
+    self.keys.borrow_mut().push(keys);
+  }
+
+  /// Queues an event, such as a mouse click, to be returned by `get_event`.
+  pub fn feed_event(&self, event: Event) {
+    self.keys.borrow_mut().push_event(event);
+  }
+
+  /// Queues the keys of a text script, see `ScriptedInput::parse`.
+  pub fn feed_script(&self, script: &str) -> Result<()> {
+    let mut keys = self.keys.borrow_mut();
+    for event in ScriptedInput::parse(script)?.remaining() {
+      keys.push_event(event);
+    }
+    Ok(())
+  }
+
+  /// Resizes the screen like a terminal window would be, keeping the cells that still fit, and queues a resize event.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::{Backend, VirtualBackend};
+  /// # use utile_cli::cli::Event;
+  /// let b = VirtualBackend::new(20, 5);
+  /// b.resize(40, 10);
+  /// assert_eq!(b.size(), (40, 10));
+  /// assert_eq!(b.get_event(), Some(Event::Resize(40, 10)));
+  /// ```
+  pub fn resize(&self, width: usize, height: usize) {
+    let (old_width, old_height) = (self.width.get(), self.height.get());
+    let mut cells = vec![" ".to_string(); width * height];
+    {
+      let old = self.cells.borrow();
+      for y in 0..height.min(old_height) {
+        for x in 0..width.min(old_width) {
+          cells[x + y * width] = old[x + y * old_width].clone();
+        }
+        // a double width cluster cut in half by the new edge
+        if width < old_width && width > 0 && width::width(&cells[width - 1 + y * width]) == 2 {
+          cells[width - 1 + y * width] = " ".to_string();
+        }
+      }
+    }
+    self.cells.replace(cells);
+    self.width.set(width);
+    self.height.set(height);
+    let (x, y) = self.cursor.get();
+    self.cursor.set((x.min(width as i32 - 1).max(0), y.min(height as i32 - 1).max(0)));
+    self.keys.borrow_mut().push_event(Event::Resize(width as i32, height as i32));
+  }
+
+  /// Returns the grapheme cluster in the cell at x and y, if it is on the screen.
+  /// 
+  /// The cell after a double width cluster is empty.
+  pub fn cell(&self, x: usize, y: usize) -> Option<String> {
+    let width = self.width.get();
+    if x < width && y < self.height.get() {
+      Some(self.cells.borrow()[x + y * width].clone())
+    } else {
+      None
+    }
+  }
+
+  /// Returns row `y` of the screen with trailing whitespace removed.
+  pub fn line(&self, y: usize) -> String {
+    let width = self.width.get();
+    if y >= self.height.get() {
+      return String::new();
+    }
+    let cells = self.cells.borrow();
+    let row: String = cells[y * width..(y + 1) * width].concat();
+    row.trim_end().to_string()
+  }
+
+  /// Returns every row of the screen with trailing whitespace removed.
+  pub fn lines(&self) -> Vec<String> {
+    (0..self.height.get()).map(|y| self.line(y)).collect()
+  }
+
+  /// Returns the screen as text, one row per line, without trailing blank rows.
+  pub fn contents(&self) -> String {
+    let mut lines = self.lines();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+      lines.pop();
+    }
+    lines.join("\n")
+  }
+
+  /// Returns the options last applied with `configure`.
+  pub fn options(&self) -> Options {
+    self.options.borrow().clone()
+  }
+
+  /// Returns true if the backend was suspended and not resumed.
+  pub fn is_suspended(&self) -> bool {
+    self.suspended.get()
+  }
+
+  /// Clears every cell and moves the cursor to the top left corner.
+  pub fn clear(&self) {
+    self.cells.borrow_mut().iter_mut().for_each(|c| *c = " ".to_string());
+    self.cursor.set((0, 0));
+  }
+
+  /// Compares the screen against the golden file at `path`, panicking with a diff if they differ.
+  /// See `snapshot::assert_snapshot`.
+  pub fn assert_snapshot<P: AsRef<Path>>(&self, path: P) {
+    snapshot::assert_snapshot(&self.contents(), path);
+  }
+
+  /// Compares the screen against `expected`, panicking with a diff if they differ.
+  pub fn assert_contents(&self, expected: &str) {
+    snapshot::assert_text(expected, &self.contents());
+  }
+
+  // blanks the other half of a double width cluster covering cell `i` of a row starting at `row`
+  fn split(&self, cells: &mut [String], row: usize, i: usize) {
+    let other = if cells[i].is_empty() && i > row {
+      i - 1
+    } else if width::width(&cells[i]) == 2 && i + 1 < row + self.width.get() {
+      i + 1
+    } else {
+      return;
+    };
+    cells[other] = " ".to_string();
+  }
+
+  // moves to the start of the next line, staying on the last line like curses does without scrolling
+  fn newline(&self, y: i32) -> (i32, i32) {
+    if (y as usize) + 1 < self.height.get() {
+      (0, y + 1)
+    } else {
+      (0, y)
+    }
+  }
+}
+
+impl Default for VirtualBackend {
+  fn default() -> VirtualBackend {
+    VirtualBackend::new(80, 24)
+  }
+}
+
+impl Backend for VirtualBackend {
+  fn out(&self, s: &str) {
+    let width = self.width.get();
+    let mut cells = self.cells.borrow_mut();
+    let (mut x, mut y) = self.cursor.get();
+    for (g, w) in width::graphemes(s) {
+      if g == "\n" {
+        let row = y as usize * width;
+        self.split(&mut cells, row, row + x as usize);
+        cells[row + x as usize..row + width].iter_mut().for_each(|c| *c = " ".to_string());
+        let (nx, ny) = self.newline(y);
+        x = nx;
+        y = ny;
+        continue;
+      }
+      if w > width {
+        continue;
+      }
+      if w == 0 {
+        // combining characters on their own join the cell before them
+        if x > 0 {
+          cells[x as usize - 1 + y as usize * width].push_str(g);
+        }
+        continue;
+      }
+      if x as usize + w > width {
+        // a double width cluster that does not fit wraps like in curses
+        let (nx, ny) = self.newline(y);
+        x = nx;
+        y = ny;
+      }
+      let row = y as usize * width;
+      let i = row + x as usize;
+      self.split(&mut cells, row, i);
+      if w == 2 {
+        self.split(&mut cells, row, i + 1);
+        cells[i + 1] = String::new();
+      }
+      cells[i] = g.to_string();
+      x += w as i32;
+      if x as usize >= width {
+        let (nx, ny) = self.newline(y);
+        x = nx;
+        y = ny;
+      }
+    }
+    self.cursor.set((x, y));
+  }
+
+  fn move_to(&self, x: i32, y: i32) {
+    if x >= 0 && y >= 0 && (x as usize) < self.width.get() && (y as usize) < self.height.get() {
+      self.cursor.set((x, y));
+    }
+  }
+
+  fn posxy(&self) -> (i32, i32) {
+    self.cursor.get()
+  }
+
+  fn delete(&self) {
+    let (x, y) = self.cursor.get();
+    let width = self.width.get();
+    let row = y as usize * width;
+    let mut cells = self.cells.borrow_mut();
+    self.split(&mut cells, row, row + x as usize);
+    cells[row + x as usize..row + width].rotate_left(1);
+    cells[row + width - 1] = " ".to_string();
+  }
+
+  fn refresh(&self) {}
+
+  fn size(&self) -> (i32, i32) {
+    (self.width.get() as i32, self.height.get() as i32)
+  }
+
+  fn get_event(&self) -> Option<Event> {
+    self.keys.borrow_mut().read_event()
+  }
+
+  fn get_event_timeout(&self, _timeout: Duration) -> Result<Option<Event>> {
+    Ok(self.keys.borrow_mut().read_event())
+  }
+
+  fn configure(&self, options: &Options) {
+    self.options.replace(options.clone());
+  }
+
+  fn suspend(&self) {
+    self.suspended.set(true);
+  }
+
+  fn resume(&self) {
+    self.suspended.set(false);
+  }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::cli::{Layer, Layer2D};
+use crate::width;
+
+/// A grid of cells which layers are composed into before being drawn.
+///
+/// Only cells covered by a layer are stored, every other cell is left alone when the buffer is drawn.
+/// Cells are ordered by row and then by column, so changes can be drawn as runs along a line.
+///
+/// Each cell holds a grapheme cluster. A double width cluster is followed by an empty cell which the terminal draws it over.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Buffer {
+  cells: BTreeMap<(i32, i32), String>
+}
+
+impl Buffer {
+  /// Returns an empty buffer.
+  pub fn new() -> Buffer {
+    Buffer { cells: BTreeMap::new() }
+  }
+
+  /// Sets the cell at x and y to a grapheme cluster covering `w` cells, cells at negative positions are ignored.
+  ///
+  /// A double width cluster also takes the next cell, and a double width cluster that is partly overwritten is replaced by spaces.
+  pub fn set(&mut self, x: i32, y: i32, g: &str, w: usize) {
+    if x < 0 || y < 0 {
+      return;
+    }
+    self.split(x, y);
+    if w == 2 {
+      self.split(x + 1, y);
+      self.cells.insert((y, x + 1), String::new());
+    }
+    self.cells.insert((y, x), g.to_string());
+  }
+
+  // blanks the other half of a double width cluster covering x and y
+  fn split(&mut self, x: i32, y: i32) {
+    let other = match self.cells.get(&(y, x)) {
+      Some(g) if g.is_empty() => x - 1,
+      Some(g) if width::width(g) == 2 => x + 1,
+      _ => return
+    };
+    self.cells.insert((y, other), " ".to_string());
+  }
+
+  /// Empties the buffer.
+  pub fn clear(&mut self) {
+    self.cells.clear();
+  }
+
+  /// Composes a layer offset by `offx` and `offy` over the buffer, including its allocated (hiding) space.
+  pub fn draw_layer(&mut self, layer: &Layer, offx: i32, offy: i32) {
+    let (mut x, y) = (layer.posx + offx, layer.posy + offy);
+    let end = x + layer.length() as i32;
+    for (g, w) in width::graphemes(&layer.get_content()) {
+      if w == 0 {
+        continue;
+      }
+      if x + w as i32 > end {
+        break;
+      }
+      self.set(x, y, g, w);
+      x += w as i32;
+    }
+    for x in x..end {
+      self.set(x, y, " ", 1);
+    }
+  }
+
+  /// Composes every layer of a layer2D over the buffer, positioned on a screen of `width` by `height` cells.
+  pub fn draw_layer2d(&mut self, layer: &Layer2D, width: i32, height: i32) {
+    let (x, y) = layer.origin(width, height);
+    for l in &layer.layers {
+      self.draw_layer(l, x, y);
+    }
+  }
+
+  /// Removes every cell outside of a screen `width` cells wide showing `rows`.
+  ///
+  /// A double width cluster in the last column is replaced by a space, as it does not fit.
+  pub fn clip(&mut self, width: i32, rows: Range<i32>) {
+    self.cells.retain(|&(y, x), _| x < width && rows.contains(&y));
+    for ((_, x), g) in self.cells.iter_mut() {
+      if *x == width - 1 && width::width(g) == 2 {
+        *g = " ".to_string();
+      }
+    }
+  }
+
+  /// Returns the runs of cells that must be drawn to turn the screen from `front` into this buffer, in the form (x, y, text).
+  ///
+  /// Cells only covered in `front` are cleared with spaces.
+  /// If `all` is true every cell of this buffer is included, even those that did not change.
+  pub fn diff(&self, front: &Buffer, all: bool) -> Vec<(i32, i32, String)> {
+    let mut changed: BTreeMap<(i32, i32), &str> = front.cells.keys()
+      .filter(|k| !self.cells.contains_key(k))
+      .map(|&k| (k, " "))
+      .collect();
+    changed.extend(self.cells.iter().filter(|(k, g)| all || front.cells.get(k) != Some(g)).map(|(&k, g)| (k, g.as_str())));
+
+    let mut runs: Vec<(i32, i32, String)> = vec![];
+    let mut next = (-1, -1);
+    for ((y, x), g) in changed {
+      match runs.last_mut() {
+        Some((_, _, s)) if next == (x, y) => s.push_str(g),
+        // the second half of a double width cluster whose first half did not change
+        _ if g.is_empty() => continue,
+        _ => runs.push((x, y, g.to_string()))
+      }
+      next = (x + 1, y);
+    }
+    runs
+  }
+}
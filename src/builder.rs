@@ -0,0 +1,194 @@
+//! Configuring a `Terminal` before it starts.
+
+use std::time::Duration;
+
+use crate::backend::{Backend, DefaultBackend};
+use crate::cli::Terminal;
+use crate::keymap::Keymap;
+use crate::keytable::KeyTable;
+
+/// How keys are read from the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputMode {
+  /// Keys are only read once Enter is pressed, the terminal's normal line buffered mode.
+  Cooked,
+  /// Keys are read as soon as they are pressed, however Ctrl-C and similar keys still send signals.
+  Cbreak,
+  /// Keys are read as soon as they are pressed, including Ctrl-C and similar keys.
+  Raw
+}
+
+/// Startup options of a terminal, applied to the backend by `TerminalBuilder`.
+///
+/// Backends apply the options they support and ignore the rest, see `Backend::configure`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+  /// How keys are read, `Cbreak` by default.
+  pub input_mode: InputMode,
+  /// Whether `get_char` echoes keys, true by default.
+  pub echo: bool,
+  /// Whether the cursor is visible, true by default.
+  pub cursor_visible: bool,
+  /// Whether reading a key returns `None` straight away if no key was pressed, false by default.
+  ///
+  /// Prompts then return `Error::TimedOut` rather than `Error::InputEnded` when no key is ready.
+  pub nodelay: bool,
+  /// How long to wait after Escape for the rest of an escape sequence or a key pressed with Alt, 25 milliseconds by default.
+  pub esc_delay: Duration,
+  /// Whether to draw on the alternate screen, leaving the normal scrollback untouched, false by default.
+  ///
+  /// Curses always uses the alternate screen if the terminal has one.
+  pub alternate_screen: bool,
+  /// Whether to install a panic hook restoring the terminal, see `Terminal::restore_on_panic`. False by default.
+  pub restore_on_panic: bool,
+  /// Whether the terminal reports mouse clicks, drags and scrolling as `Event::Mouse`, false by default.
+  /// 
+  /// While reporting is on, selecting text with the mouse usually needs Shift held.
+  pub mouse: bool,
+  /// Whether pasted text is read as a single `Event::Paste` instead of one key per character, true by default.
+  /// 
+  /// Terminals without bracketed paste send the keys as usual.
+  pub bracketed_paste: bool,
+  /// How raw input is mapped to keys, `KeyTable::default` by default.
+  pub key_table: KeyTable,
+  /// Which keys submit, cancel and move through prompts, `Keymap::default` by default.
+  pub keymap: Keymap,
+  /// How long prompts wait for each key before returning `Error::TimedOut`, `None` (forever) by default.
+  pub prompt_timeout: Option<Duration>
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options {
+      input_mode: InputMode::Cbreak,
+      echo: true,
+      cursor_visible: true,
+      nodelay: false,
+      esc_delay: Duration::from_millis(25),
+      alternate_screen: false,
+      restore_on_panic: false,
+      mouse: false,
+      bracketed_paste: true,
+      key_table: KeyTable::default(),
+      keymap: Keymap::default(),
+      prompt_timeout: None
+    }
+  }
+}
+
+/// Sets up the input mode and other startup options of a terminal, and chooses its backend.
+///
+/// # Examples
+/// ```no_run
+/// # use utile_cli::backend::AnsiBackend;
+/// # use utile_cli::builder::{InputMode, TerminalBuilder};
+/// let t = TerminalBuilder::new()
+///   .input_mode(InputMode::Raw)
+///   .echo(false)
+///   .cursor_visible(false)
+///   .alternate_screen(true)
+///   .build_with(AnsiBackend::stdio());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TerminalBuilder {
+  options: Options
+}
+
+impl TerminalBuilder {
+  /// Returns a builder with the default options.
+  pub fn new() -> TerminalBuilder {
+    TerminalBuilder { options: Options::default() }
+  }
+
+  /// Sets how keys are read.
+  pub fn input_mode(mut self, mode: InputMode) -> TerminalBuilder {
+    self.options.input_mode = mode;
+    self
+  }
+
+  /// Sets whether `get_char` echoes keys.
+  pub fn echo(mut self, echo: bool) -> TerminalBuilder {
+    self.options.echo = echo;
+    self
+  }
+
+  /// Sets whether the cursor is visible.
+  pub fn cursor_visible(mut self, visible: bool) -> TerminalBuilder {
+    self.options.cursor_visible = visible;
+    self
+  }
+
+  /// Sets whether reading a key returns `None` straight away if no key was pressed.
+  pub fn nodelay(mut self, nodelay: bool) -> TerminalBuilder {
+    self.options.nodelay = nodelay;
+    self
+  }
+
+  /// Sets how long to wait after Escape for the rest of an escape sequence or a key pressed with Alt.
+  pub fn esc_delay(mut self, delay: Duration) -> TerminalBuilder {
+    self.options.esc_delay = delay;
+    self
+  }
+
+  /// Sets whether to draw on the alternate screen.
+  pub fn alternate_screen(mut self, alternate: bool) -> TerminalBuilder {
+    self.options.alternate_screen = alternate;
+    self
+  }
+
+  /// Sets whether to install a panic hook restoring the terminal.
+  pub fn restore_on_panic(mut self, restore: bool) -> TerminalBuilder {
+    self.options.restore_on_panic = restore;
+    self
+  }
+
+  /// Sets whether the terminal reports mouse events.
+  pub fn mouse(mut self, mouse: bool) -> TerminalBuilder {
+    self.options.mouse = mouse;
+    self
+  }
+
+  /// Sets whether pasted text is read as a single event.
+  pub fn bracketed_paste(mut self, paste: bool) -> TerminalBuilder {
+    self.options.bracketed_paste = paste;
+    self
+  }
+
+  /// Sets how raw input is mapped to keys.
+  pub fn key_table(mut self, table: KeyTable) -> TerminalBuilder {
+    self.options.key_table = table;
+    self
+  }
+
+  /// Sets which keys submit, cancel and move through prompts.
+  pub fn keymap(mut self, keymap: Keymap) -> TerminalBuilder {
+    self.options.keymap = keymap;
+    self
+  }
+
+  /// Sets how long prompts wait for each key before timing out.
+  pub fn prompt_timeout(mut self, timeout: Duration) -> TerminalBuilder {
+    self.options.prompt_timeout = Some(timeout);
+    self
+  }
+
+  /// Returns the options that will be applied.
+  pub fn options(&self) -> &Options {
+    &self.options
+  }
+
+  /// Creates a terminal over the default backend, see `DefaultBackend`.
+  pub fn build(self) -> Terminal {
+    self.build_with(DefaultBackend::default())
+  }
+
+  /// Creates a terminal over `backend`.
+  pub fn build_with<B: Backend>(self, backend: B) -> Terminal<B> {
+    backend.configure(&self.options);
+    let t = Terminal::unconfigured(backend, &self.options);
+    if self.options.restore_on_panic {
+      t.restore_on_panic();
+    }
+    t
+  }
+}
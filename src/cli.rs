@@ -1,18 +1,62 @@
-extern crate pancurses;
-use pancurses::{Window, Input, initscr};
+use std::cell::{Cell, Ref, RefCell};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant};
 
-/// A terminal containing a pancurses window.
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::app::{App, Control};
+use crate::backend::{Backend, DefaultBackend};
+use crate::builder::{Options, TerminalBuilder};
+use crate::editor::{LineEditor, TextEditor};
+use crate::buffer::Buffer;
+use crate::error::{Error, Result};
+use crate::complete::common_prefix;
+use crate::history::History;
+use crate::input::InputSource;
+use crate::keymap::{Action, Keymap};
+use crate::prompt::{AskOptions, Number, NumberOptions, PasswordOptions, TextAreaOptions};
+use crate::secret::Secret;
+use crate::width;
+
+// the restorers of the live terminals which called `restore_on_panic`, by terminal id
+type Restorers = Vec<(usize, Box<dyn Fn() + Send + Sync>)>;
+static RESTORERS: Mutex<Restorers> = Mutex::new(Vec::new());
+static RESTORER_IDS: AtomicUsize = AtomicUsize::new(0);
+static PANIC_HOOK: Once = Once::new();
+
+/// A terminal drawing to a `Backend`, by default a pancurses window (see `DefaultBackend`).
 /// 
 /// Contains multiple abstractions over pancurses.
 /// 
 /// # Examples
 /// Creating a terminal:
+/// ```no_run
+/// # use utile_cli::cli::Terminal;
+/// let t = Terminal::new();
 /// ```
-/// let t = Terminal::new()
+/// 
+/// Creating a terminal over another backend:
+/// ```no_run
+/// # use utile_cli::backend::AnsiBackend;
+/// # use utile_cli::cli::Terminal;
+/// let t = Terminal::with_backend(AnsiBackend::stdio());
 /// ```
-pub struct Terminal {
-  win: Window,
-  layers: LayerArrangement
+pub struct Terminal<B: Backend = DefaultBackend> {
+  backend: B,
+  input: RefCell<Option<Box<dyn InputSource>>>,
+  echo: bool,
+  nodelay: bool,
+  keymap: Keymap,
+  prompt_timeout: Cell<Option<Duration>>,
+  history: RefCell<Option<History>>,
+  layers: LayerArrangement,
+  front: RefCell<Buffer>,
+  back: RefCell<Buffer>,
+  restorer_id: Cell<Option<usize>>
 }
 
 struct LayerArrangement {
@@ -31,24 +75,33 @@ struct LayerArrangement {
 /// # Examples
 /// A layer can be initialized using `new`:
 /// ```
-/// let layer = Layer::new(0, 0);
+/// # use utile_cli::cli::*;
+/// let mut layer = Layer::new(0, 0);
 /// layer.set_content("Hello world!".into());
 /// ```
 /// 
 /// A layer can then be displayed from a terminal either by being added using `add_layer` and then displayed using `refresh`:
-/// ```
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # let mut t = Terminal::new();
+/// # let layer = Layer::new(0, 0);
 /// let new: &mut Layer = t.add_layer(layer);
 /// t.refresh();
 /// ```
 /// 
 /// Or it can be displayed externally by using `draw_layer`:
-/// ```
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # let mut t = Terminal::new();
+/// # let layer = Layer::new(0, 0);
 /// t.draw_layer(&layer);
 /// t.draw_layer_static(&layer); // <- a layer can also be drawn without editting the cursor position
 /// ```
 /// 
 /// A layer also contains another type of content called `inner_content` which will *never* be displayed to the terminal, however may contain useful data about the layer:
 /// ```
+/// # use utile_cli::cli::*;
+/// # let mut layer = Layer::new(0, 0);
 /// layer.inner_content = "Hello rust!".into();
 /// layer.inner_to_outer(); // <- replaces the outer content with the inner content.
 /// ```
@@ -67,6 +120,7 @@ pub struct Layer {
 /// # Examples
 /// A Layer2D can be initialized using `new`:
 /// ```
+/// # use utile_cli::cli::*;
 /// let l = Layer::new(0, 0);
 /// let l2d = Layer2D::new(0, 0, 5, 5, l);
 /// ```
@@ -75,7 +129,9 @@ pub struct Layer {
 /// 
 /// A Layer2D can be populated again using the `populate` method:
 /// ```
-/// let l2 = Layer::new(0, 0);
+/// # use utile_cli::cli::*;
+/// # let mut l2d = Layer2D::new(0, 0, 5, 5, Layer::new(0, 0));
+/// let mut l2 = Layer::new(0, 0);
 /// l2.set_content("X".into());
 /// l2d.populate(l2);
 /// ```
@@ -83,20 +139,40 @@ pub struct Layer {
 /// 
 /// Any layer in a Layer2D can be retrieved using `index` or `get`:
 /// ```
+/// # use utile_cli::cli::*;
+/// # let mut l2d = Layer2D::new(0, 0, 5, 5, Layer::new(0, 0));
 /// l2d.index(3, 4); // returns a *mutable* Layer (&mut Layer)
 /// l2d.get(3, 4);   // returns a *immutable* Layer (&Layer)
 /// ```
 /// 
 /// A layer can be displayed from a terminal using `add_layer2d` and `refresh`, much like regular layers:
-/// ```
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # let mut t = Terminal::new();
+/// # let l2d = Layer2D::new(0, 0, 5, 5, Layer::new(0, 0));
 /// t.add_layer2d(l2d); // <- returns a &mut Layer2D which can be editted
 /// t.refresh();
 /// ```
 /// 
 /// Or it can be displayed externally by using `draw_layer2d`:
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # let mut t = Terminal::new();
+/// # let l2d = Layer2D::new(0, 0, 5, 5, Layer::new(0, 0));
+/// t.draw_layer2d(&l2d);
+/// t.draw_layer2d_static(&l2d); // <- a layer can also be drawn without editting the cursor position
 /// ```
-/// t.draw_layer2d(&layer);
-/// t.draw_layer2d_static(&layer); // <- a layer can also be drawn without editting the cursor position
+/// 
+/// A Layer2D added to a terminal can be anchored to a side or corner of the screen, keeping it in place when the terminal is resized:
+/// ```
+/// # use utile_cli::backend::{Backend, VirtualBackend};
+/// # use utile_cli::cli::*;
+/// let mut t = Terminal::with_backend(VirtualBackend::new(20, 5));
+/// let mut l = Layer::new(0, 0);
+/// l.set_content("#".into());
+/// t.add_layer2d(Layer2D::new(0, 0, 2, 1, l)).set_anchor(Anchor::BottomRight);
+/// t.refresh();
+/// assert_eq!(t.backend().line(4), "                  ##");
 /// ```
 #[derive(Clone, Debug)]
 pub struct Layer2D {
@@ -105,23 +181,197 @@ pub struct Layer2D {
   pub length: usize,
   pub height: usize,
   pub layers: Vec<Layer>,
+  /// The point of the screen that `posx` and `posy` are relative to, `Anchor::TopLeft` by default.
+  pub anchor: Anchor,
   char_count: usize,
   stack_loc: i32
 }
 
-#[derive(Debug, PartialEq)]
+/// The side or corner of the screen that a Layer2D is positioned from, see `Layer2D::origin`.
+/// 
+/// Anchored Layer2Ds are repositioned whenever the terminal is resized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight
+}
+
+/// A key read from input.
+/// 
+/// Characters carry the modifiers they were typed with, other keys are wrapped in `Modified` when a modifier is held.
+/// 
+/// # Examples
+/// ```
+/// # use utile_cli::cli::*;
+/// assert_eq!(Key::ctrl('c'), Key::Char { c: 'c', mods: Modifiers::CTRL });
+/// assert_eq!(Key::Home.with_mods(Modifiers::SHIFT).mods(), Modifiers::SHIFT);
+/// assert_eq!(Key::char('a').printable(), Some('a'));
+/// assert_eq!(Key::alt('a').printable(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Key {
-  Alpha(char),
+  /// A character, along with the modifiers held.
+  /// Shift is not set for printable characters as it is already part of the character, and Ctrl characters are lowercase.
+  Char { c: char, mods: Modifiers },
   Enter,
+  Tab,
+  BackTab,
+  Escape,
   Space,
   Backspace,
+  Delete,
+  Insert,
+  Home,
+  End,
+  PageUp,
+  PageDown,
   ArrowUp,
   ArrowDown,
   ArrowLeft,
   ArrowRight,
-  F1,F2,F3,F4,F5,F6,F7,F8,F9,F10,F11,F12
+  F1,F2,F3,F4,F5,F6,F7,F8,F9,F10,F11,F12,
+  /// A key other than a character pressed with modifiers, such as Ctrl + ArrowLeft.
+  Modified { key: Box<Key>, mods: Modifiers }
+}
+
+/// The modifier keys held while a key was pressed.
+/// 
+/// Modifiers can be combined with `|`, for example `Modifiers::CTRL | Modifiers::SHIFT`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+  pub ctrl: bool,
+  pub alt: bool,
+  pub shift: bool
+}
+
+/// An input event, either a key or a change to the terminal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+  /// A key was pressed.
+  Key(Key),
+  /// The terminal was resized, in the form (width, height).
+  Resize(i32, i32),
+  /// A mouse button was pressed, released or dragged, or the wheel was scrolled.
+  /// Only read once mouse reporting is turned on with `TerminalBuilder::mouse`.
+  Mouse(MouseEvent),
+  /// Text was pasted, see `TerminalBuilder::bracketed_paste`.
+  Paste(String),
+  /// The tick rate of an app passed, only sent by `Terminal::run`, see `App::tick_rate`.
+  Tick
+}
+
+/// A mouse action at a cell of the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+  pub kind: MouseKind,
+  pub x: i32,
+  pub y: i32,
+  /// The modifiers held, which some terminals keep for themselves.
+  pub mods: Modifiers
+}
+
+/// What the mouse did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseKind {
+  Press(MouseButton),
+  Release(MouseButton),
+  /// The mouse moved while the button was held.
+  Drag(MouseButton),
+  ScrollUp,
+  ScrollDown
+}
+
+/// A mouse button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+  Left,
+  Middle,
+  Right
+}
+
+
+impl Key {
+  /// Returns a character key without modifiers.
+  pub fn char(c: char) -> Key {
+    Key::Char { c, mods: Modifiers::NONE }
+  }
+
+  /// Returns a character key with Ctrl held.
+  pub fn ctrl(c: char) -> Key {
+    Key::Char { c, mods: Modifiers::CTRL }
+  }
+
+  /// Returns a character key with Alt held.
+  pub fn alt(c: char) -> Key {
+    Key::Char { c, mods: Modifiers::ALT }
+  }
+
+  /// Returns the key with `mods` held as well as its own modifiers.
+  pub fn with_mods(self, mods: Modifiers) -> Key {
+    match self {
+      Key::Char { c, mods: m } => Key::Char { c, mods: m | mods },
+      Key::Modified { key, mods: m } => Key::Modified { key, mods: m | mods },
+      key if mods.is_empty() => key,
+      key => Key::Modified { key: Box::new(key), mods }
+    }
+  }
+
+  /// Returns the modifiers held.
+  pub fn mods(&self) -> Modifiers {
+    match self {
+      Key::Char { mods, .. } | Key::Modified { mods, .. } => *mods,
+      _ => Modifiers::NONE
+    }
+  }
+
+  /// Returns the character that typing the key inserts, which is none if Ctrl or Alt is held.
+  pub fn printable(&self) -> Option<char> {
+    match self {
+      Key::Char { c, mods } if !mods.ctrl && !mods.alt && !c.is_control() => Some(*c),
+      Key::Space => Some(' '),
+      _ => None
+    }
+  }
+}
+
+impl MouseEvent {
+  /// Returns a mouse event at x and y without modifiers.
+  pub fn new(kind: MouseKind, x: i32, y: i32) -> MouseEvent {
+    MouseEvent { kind, x, y, mods: Modifiers::NONE }
+  }
+
+  /// Returns true if this is a press of the left button.
+  pub fn is_click(&self) -> bool {
+    self.kind == MouseKind::Press(MouseButton::Left)
+  }
 }
 
+impl Modifiers {
+  pub const NONE: Modifiers = Modifiers { ctrl: false, alt: false, shift: false };
+  pub const CTRL: Modifiers = Modifiers { ctrl: true, alt: false, shift: false };
+  pub const ALT: Modifiers = Modifiers { ctrl: false, alt: true, shift: false };
+  pub const SHIFT: Modifiers = Modifiers { ctrl: false, alt: false, shift: true };
+
+  /// Returns true if no modifier is held.
+  pub fn is_empty(&self) -> bool {
+    *self == Modifiers::NONE
+  }
+}
+
+impl std::ops::BitOr for Modifiers {
+  type Output = Modifiers;
+
+  fn bitor(self, other: Modifiers) -> Modifiers {
+    Modifiers { ctrl: self.ctrl || other.ctrl, alt: self.alt || other.alt, shift: self.shift || other.shift }
+  }
+}
 
 impl Layer {
   /// Returns a new layer at `posx`, `posy`
@@ -134,11 +384,17 @@ impl Layer {
     self.content.clone()
   }
 
+  // zeroes the outer content, leaving it empty
+  pub(crate) fn wipe(&mut self) {
+    self.content.zeroize();
+  }
+
   /// Sets the current outer content of the layer.
   pub fn set_content(&mut self, c: String) -> &mut Layer {
     self.content = c;
-    if self.content.len() > self.length {
-      self.length = self.content.len();
+    let width = width::width(&self.content);
+    if width > self.length {
+      self.length = width;
     }
     self
   }
@@ -150,7 +406,19 @@ impl Layer {
 
   /// Removes any hiding content.
   pub fn shrink(&mut self) {
-    self.length = self.content.len();
+    self.length = width::width(&self.content);
+  }
+
+  /// Returns true if the cell at x and y is within the allocated space of the layer.
+  pub fn contains(&self, x: i32, y: i32) -> bool {
+    y == self.posy && x >= self.posx && x < self.posx + self.length as i32
+  }
+
+  /// Returns the allocated length of the layer, which is the length of its longest content since the last `shrink`.
+  /// 
+  /// Lengths are measured in terminal cells, so a double width character such as `字` counts twice and a combining accent not at all.
+  pub fn length(&self) -> usize {
+    self.length
   }
 }
 
@@ -158,15 +426,18 @@ impl Layer2D {
   /// Returns a new layer2d at `posx`, `posy` with length and height.
   /// Position is determined from the top left corner.
   pub fn new(posx: i32, posy: i32, length: usize, height: usize, populator: Layer) -> Layer2D {
-    let mut l = Layer2D { posx, posy, length, height, layers: vec![], char_count: 0, stack_loc: 0 };
+    let mut l = Layer2D { posx, posy, length, height, layers: vec![], anchor: Anchor::TopLeft, char_count: 0, stack_loc: 0 };
     l.populate(populator);
     l
   }
 
   /// Populates a layer2d with `populator`
+  /// 
+  /// Columns are as wide as the populator's content in terminal cells, so a grid of `字` has columns two cells wide.
+  /// A cell whose content is later made wider than its column is overlapped by the next cell, which blanks a double width character it covers half of.
   pub fn populate(&mut self, populator: Layer) {
     let n = populator.clone();
-    self.char_count = n.get_content().len();
+    self.char_count = width::width(&n.get_content());
     self.layers = std::iter::repeat(n).take(self.length * self.height)
                               .enumerate()
                               .map(|(i, l)| { 
@@ -178,13 +449,70 @@ impl Layer2D {
   }
 
   /// Returns a *mutable* reference to the layer at x and y
+  /// 
+  /// # Panics
+  /// Panics if x or y is outside of the layer2D, see `try_index`.
   pub fn index(&mut self, x: usize, y: usize) -> &mut Layer {
-    &mut self.layers[x + y * self.length]
+    self.try_index(x, y).unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Returns a *mutable* reference to the layer at x and y, or `Error::OutOfBounds`.
+  pub fn try_index(&mut self, x: usize, y: usize) -> Result<&mut Layer> {
+    let i = self.cell_idx(x, y)?;
+    Ok(&mut self.layers[i])
   }
 
   /// Returns a *immutable* reference to the layer at x and y
+  /// 
+  /// # Panics
+  /// Panics if x or y is outside of the layer2D, see `try_get`.
   pub fn get(&self, x: usize, y: usize) -> &Layer {
-    &self.layers[x + y * self.length]
+    self.try_get(x, y).unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Returns a *immutable* reference to the layer at x and y, or `Error::OutOfBounds`.
+  pub fn try_get(&self, x: usize, y: usize) -> Result<&Layer> {
+    let i = self.cell_idx(x, y)?;
+    Ok(&self.layers[i])
+  }
+
+  /// Sets the point of the screen that the position is relative to.
+  pub fn set_anchor(&mut self, anchor: Anchor) -> &mut Layer2D {
+    self.anchor = anchor;
+    self
+  }
+
+  /// Returns the number of cells covered by the layer2D in the form (width, height)
+  pub fn dimensions(&self) -> (i32, i32) {
+    self.layers.iter()
+      .fold((0, 0), |(w, h), l| (w.max(l.posx + l.length() as i32), h.max(l.posy + 1)))
+  }
+
+  /// Returns the top left corner of the layer2D on a screen of `width` by `height` cells.
+  /// 
+  /// `posx` and `posy` are measured inwards from the anchored side, so a layer2D anchored to the right with a `posx` of 1 ends one cell from the right edge.
+  /// Centered layer2Ds are moved right and down by their position instead.
+  pub fn origin(&self, width: i32, height: i32) -> (i32, i32) {
+    let (w, h) = self.dimensions();
+    let x = match self.anchor {
+      Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => self.posx,
+      Anchor::Top | Anchor::Center | Anchor::Bottom => (width - w) / 2 + self.posx,
+      Anchor::TopRight | Anchor::Right | Anchor::BottomRight => width - w - self.posx
+    };
+    let y = match self.anchor {
+      Anchor::TopLeft | Anchor::Top | Anchor::TopRight => self.posy,
+      Anchor::Left | Anchor::Center | Anchor::Right => (height - h) / 2 + self.posy,
+      Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => height - h - self.posy
+    };
+    (x, y)
+  }
+
+  fn cell_idx(&self, x: usize, y: usize) -> Result<usize> {
+    if x < self.length && y < self.height {
+      Ok(x + y * self.length)
+    } else {
+      Err(Error::OutOfBounds(x, y))
+    }
   }
 }
 
@@ -204,20 +532,194 @@ impl LayerArrangement {
 }
 
 // locate index in stack
-fn locate_idx(len: usize, l: i32) -> usize {
-  match l <= 0 {
-    false => l as usize,
-    true => ((len - 1) as i32 + l) as usize
+fn locate_idx(len: usize, l: i32) -> Result<usize> {
+  let idx = match l <= 0 {
+    false => l as i64,
+    true => len as i64 - 1 + l as i64
+  };
+  if idx >= 0 && idx < len as i64 {
+    Ok(idx as usize)
+  } else {
+    Err(Error::LayerNotFound(l))
   }
 }
 
-impl Terminal {
+// the registered restorers, still usable after a panic while they were locked
+fn restorers() -> std::sync::MutexGuard<'static, Restorers> {
+  RESTORERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// the characters of pasted text a one line prompt takes, with newlines as spaces so they do not submit it
+// and without those at the end, as copying a line often copies its newline
+fn pasted(text: &str) -> impl Iterator<Item = char> + '_ {
+  text.trim_end_matches('\n').chars()
+    .map(|c| if c == '\n' { ' ' } else { c })
+    .filter(|&c| c == '\t' || !c.is_control())
+}
+
+// the layer of a prompt's line, wiped once dropped as it may hold a revealed password
+struct Wiped(Layer);
+
+impl std::ops::Deref for Wiped {
+  type Target = Layer;
+
+  fn deref(&self) -> &Layer {
+    &self.0
+  }
+}
+
+impl std::ops::DerefMut for Wiped {
+  fn deref_mut(&mut self) -> &mut Layer {
+    &mut self.0
+  }
+}
 
-  /// Creates a new terminal
+impl Drop for Wiped {
+  fn drop(&mut self) {
+    self.0.wipe();
+  }
+}
+
+impl Terminal {
+  /// Creates a new terminal with the default options, see `TerminalBuilder` to change them.
   pub fn new() -> Terminal {
-    let win = initscr();
-    win.keypad(true);
-    Terminal { win, layers: LayerArrangement::new() }
+    TerminalBuilder::new().build()
+  }
+}
+
+impl Default for Terminal {
+  fn default() -> Terminal {
+    Terminal::new()
+  }
+}
+
+impl<B: Backend> Drop for Terminal<B> {
+  /// Restores the terminal to its normal mode, see `suspend`.
+  fn drop(&mut self) {
+    if let Some(id) = self.restorer_id.take() {
+      restorers().retain(|(other, _)| *other != id);
+    }
+    self.suspend();
+  }
+}
+
+impl<B: Backend> Terminal<B> {
+  /// Creates a new terminal drawing to `backend` with the default options, see `TerminalBuilder` to change them.
+  pub fn with_backend(backend: B) -> Terminal<B> {
+    TerminalBuilder::new().build_with(backend)
+  }
+
+  // creates a terminal without configuring the backend
+  pub(crate) fn unconfigured(backend: B, options: &Options) -> Terminal<B> {
+    Terminal {
+      backend,
+      echo: options.echo,
+      nodelay: options.nodelay,
+      keymap: options.keymap.clone(),
+      prompt_timeout: Cell::new(options.prompt_timeout),
+      history: RefCell::new(None),
+      input: RefCell::new(None),
+      layers: LayerArrangement::new(),
+      front: RefCell::new(Buffer::new()),
+      back: RefCell::new(Buffer::new()),
+      restorer_id: Cell::new(None)
+    }
+  }
+
+  /// Returns the backend the terminal draws to.
+  pub fn backend(&self) -> &B {
+    &self.backend
+  }
+
+  /// Returns the keymap consulted by prompts.
+  pub fn keymap(&self) -> &Keymap {
+    &self.keymap
+  }
+
+  /// Sets which keys submit, cancel and move through prompts.
+  /// 
+  /// # Examples
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # use utile_cli::keymap::Keymap;
+  /// let mut t = Terminal::new();
+  /// t.set_keymap(Keymap::load("keys.conf").unwrap_or_default());
+  /// ```
+  pub fn set_keymap(&mut self, keymap: Keymap) {
+    self.keymap = keymap;
+  }
+
+  /// Sets how long prompts wait for each key before their `try_*` versions return `Error::TimedOut`, `None` to wait forever.
+  /// 
+  /// The other versions treat a timeout like the end of input.
+  pub fn set_prompt_timeout(&self, timeout: Option<Duration>) {
+    self.prompt_timeout.set(timeout);
+  }
+
+  /// Sets the history `ask` adds its answers to and lets the user walk through, `None` for no history.
+  pub fn set_history(&mut self, history: Option<History>) {
+    self.history = RefCell::new(history);
+  }
+
+  /// Returns the history set with `set_history`.
+  pub fn history(&self) -> Ref<'_, Option<History>> {
+    self.history.borrow()
+  }
+
+  /// Reads keys from `input` instead of the backend.
+  pub fn set_input<I: InputSource + 'static>(&mut self, input: I) {
+    self.input = RefCell::new(Some(Box::new(input)));
+  }
+
+  /// Removes the input source set with `set_input`, returning to reading keys from the backend.
+  pub fn reset_input(&mut self) -> Option<Box<dyn InputSource>> {
+    self.input.get_mut().take()
+  }
+
+  /// Restores the terminal to its normal mode, for example to run another program.
+  /// Call `resume` to return to the terminal.
+  /// 
+  /// # Examples
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
+  /// t.suspend();
+  /// std::process::Command::new("vi").status().unwrap();
+  /// t.resume();
+  /// ```
+  pub fn suspend(&self) {
+    self.backend.refresh();
+    self.backend.suspend();
+  }
+
+  /// Returns to the terminal after `suspend`, re-drawing every layer.
+  pub fn resume(&self) {
+    self.backend.resume();
+    self.redraw();
+  }
+
+  /// Installs a panic hook which restores the terminal before the panic message is printed.
+  /// 
+  /// The terminal is also restored when it is dropped, however while unwinding that happens after the message is printed.
+  /// The hook is installed once for every terminal, calling this again does nothing, and a dropped terminal is no longer restored.
+  pub fn restore_on_panic(&self) {
+    if self.restorer_id.get().is_some() {
+      return;
+    }
+    if let Some(restore) = self.backend.restorer() {
+      PANIC_HOOK.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+          for (_, restore) in restorers().iter() {
+            restore();
+          }
+          hook(info);
+        }));
+      });
+      let id = RESTORER_IDS.fetch_add(1, Ordering::Relaxed);
+      restorers().push((id, restore));
+      self.restorer_id.set(Some(id));
+    }
   }
 
   // Adds a layer to the bottom of the layer 'queue'
@@ -235,30 +737,112 @@ impl Terminal {
   }
 
   /// Refreshes and re-draws all layers.
+  /// 
+  /// Layers are composed into a back buffer, and only the cells that changed since the last refresh are drawn.
+  /// Cells that are no longer covered by any layer are cleared.
   pub fn refresh(&self) {
-    self.win.refresh();
-    let here = self.raw_posxy();
+    self.render(false);
+  }
+
+  /// Re-draws every layer, even the cells that did not change.
+  /// 
+  /// Useful when the screen was changed without the terminal knowing, such as output over a layer.
+  pub fn redraw(&self) {
+    self.render(true);
+  }
+
+  // composes the layers into the back buffer and draws it, clearing cells only covered by the front buffer
+  fn render(&self, all: bool) {
+    let (width, height) = self.size();
+    let mut back = self.back.borrow_mut();
+    let mut front = self.front.borrow_mut();
+    back.clear();
     for l2d in &self.layers.layer_stack {
-      self.draw_layer2d(l2d);
+      back.draw_layer2d(l2d, width, height);
     }
-    self.raw_move(here.0, here.1);
-    self.win.refresh();
+    back.clip(width, self.visible_rows());
+    front.clip(width, self.visible_rows());
+    let changes = back.diff(&front, all);
+    if !changes.is_empty() {
+      let here = self.raw_posxy();
+      for (x, y, s) in changes {
+        self.raw_move(x, y);
+        self.raw_out(s);
+      }
+      self.raw_move(here.0, here.1);
+    }
+    std::mem::swap(&mut *back, &mut *front);
+    self.backend.refresh();
+  }
+
+  /// Returns the size of the screen in the form (width, height)
+  pub fn size(&self) -> (i32, i32) {
+    self.backend.size()
+  }
+
+  // the rows on the screen, which move down once the output of a backend like `AnsiBackend` scrolls
+  fn visible_rows(&self) -> Range<i32> {
+    let top = self.backend.top_row();
+    top..top + self.size().1
   }
 
   /// Returns the layer at the front.
+  /// 
+  /// # Panics
+  /// Panics if there are no layers, see `try_layer_front`.
   pub fn layer_front(&self) -> &Layer2D {
-    self.layers.layer_stack.last().unwrap()
+    self.try_layer_front().unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Returns the layer at the front, or `Error::EmptyLayerStack`.
+  pub fn try_layer_front(&self) -> Result<&Layer2D> {
+    self.layers.layer_stack.last().ok_or(Error::EmptyLayerStack)
+  }
+
+  /// Removes and returns the layer at the front.
+  pub fn layer_pop(&mut self) -> Option<Layer2D> {
+    self.layers.pop()
+  }
+
+  /// Returns a *mutable* reference to the layer at the front.
+  /// 
+  /// # Panics
+  /// Panics if there are no layers, see `try_layer_front_mut`.
+  pub fn layer_front_mut(&mut self) -> &mut Layer2D {
+    self.try_layer_front_mut().unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Returns a *mutable* reference to the layer at the front, or `Error::EmptyLayerStack`.
+  pub fn try_layer_front_mut(&mut self) -> Result<&mut Layer2D> {
+    self.layers.layer_stack.last_mut().ok_or(Error::EmptyLayerStack)
   }
 
   /// Returns the layer at the back.
+  /// 
+  /// # Panics
+  /// Panics if there are no layers, see `try_layer_back`.
   pub fn layer_back(&self) -> &Layer2D {
-    self.layers.layer_stack.first().unwrap()
+    self.try_layer_back().unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Returns the layer at the back, or `Error::EmptyLayerStack`.
+  pub fn try_layer_back(&self) -> Result<&Layer2D> {
+    self.layers.layer_stack.first().ok_or(Error::EmptyLayerStack)
   }
 
   /// Swaps two layers. Check the `layer_locate` documentation about the number parameters.
+  /// 
+  /// # Panics
+  /// Panics if either layer does not exist, see `try_layer_swap`.
   pub fn layer_swap(&mut self, a: i32, b: i32) {
+    self.try_layer_swap(a, b).unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Swaps two layers, or returns `Error::LayerNotFound` if either layer does not exist.
+  pub fn try_layer_swap(&mut self, a: i32, b: i32) -> Result<()> {
     let len = self.layers.layer_stack.len();
-    self.layers.layer_stack.swap(locate_idx(len, a), locate_idx(len, b));
+    self.layers.layer_stack.swap(locate_idx(len, a)?, locate_idx(len, b)?);
+    Ok(())
   }
 
   /// Locates a layer by the distance to the stack or by the last item -
@@ -269,21 +853,55 @@ impl Terminal {
   /// 
   /// # Examples:
   /// Imagine a stack:
-  /// ```
+  /// ```text
   /// [ L1 ] <- `layer_locate(0)` or `layer_front()`
   /// [ L2 ] <- `layer_locate(-1)`
   /// [ L3 ] <- `layer_locate(1)` or `layer_locate(-2)`
   /// [ L4 ] <- `layer_back()`
   /// ```
+  /// 
+  /// # Panics
+  /// Panics if there is no layer at `l`, see `try_layer_locate`.
   pub fn layer_locate(&self, l: i32) -> &Layer2D {
-    &self.layers.layer_stack[locate_idx(self.layers.layer_stack.len(), l)]
+    self.try_layer_locate(l).unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  /// Locates a layer like `layer_locate`, or returns `Error::LayerNotFound` if there is no layer at `l`.
+  pub fn try_layer_locate(&self, l: i32) -> Result<&Layer2D> {
+    Ok(&self.layers.layer_stack[locate_idx(self.layers.layer_stack.len(), l)?])
+  }
+
+  /// Returns the front-most layer2D covering the cell at x and y on the screen, along with its layer covering the cell.
+  /// 
+  /// # Examples
+  /// Finding the layer that was clicked:
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
+  /// if let Some(Event::Mouse(m)) = t.get_event() {
+  ///   if let Some((_, layer)) = t.layer_at(m.x, m.y) {
+  ///     t.outln(layer.get_content());
+  ///   }
+  /// }
+  /// ```
+  pub fn layer_at(&self, x: i32, y: i32) -> Option<(&Layer2D, &Layer)> {
+    let (width, height) = self.size();
+    self.layers.layer_stack.iter().rev().find_map(|l2d| {
+      let (ox, oy) = l2d.origin(width, height);
+      l2d.layers.iter().find(|l| l.contains(x - ox, y - oy)).map(|l| (l2d, l))
+    })
   }
 
   /// Draws a layer to the console.
   pub fn draw_layer(&self, layer: &Layer) {
-    self.raw_move(layer.posx, layer.posy);
+    self.draw_layer_at(layer, 0, 0);
+  }
+
+  // draws a layer offset by its layer2D's position
+  fn draw_layer_at(&self, layer: &Layer, offx: i32, offy: i32) {
+    self.raw_move(layer.posx + offx, layer.posy + offy);
     self.raw_out_static(" ".repeat(layer.length)); // clear layer
-    self.raw_out(layer.get_content());
+    self.backend.out(&layer.content);
   }
 
   /// Draws a layer to the console however does not affect the cursor.
@@ -294,21 +912,26 @@ impl Terminal {
   }
 
   /// Draws a layer2D to the console.
+  /// 
+  /// Every layer is drawn relative to the position of the layer2D.
   pub fn draw_layer2d(&self, layer: &Layer2D) {
-    self.raw_move(layer.posx, layer.posy);
-    layer.layers.iter().for_each(|l| self.draw_layer_static(l) );
+    let here = self.raw_posxy();
+    let (width, height) = self.size();
+    let (x, y) = layer.origin(width, height);
+    layer.layers.iter().for_each(|l| self.draw_layer_at(l, x, y) );
+    self.raw_move(here.0, here.1);
   }
 
   /// Draws a layer2D to the console however does not affect the cursor.
-  pub fn draw_layer2d_static(&self, layer: &Layer) {
+  pub fn draw_layer2d_static(&self, layer: &Layer2D) {
     let here = self.raw_posxy();
-    self.draw_layer(layer);
+    self.draw_layer2d(layer);
     self.raw_move(here.0, here.1);
   }
   
   /// Outputs a string over the cursor.
   pub fn out(&self, s: String) {
-    self.win.printw(s);
+    self.backend.out(&s);
     self.refresh();
   }
 
@@ -321,19 +944,19 @@ impl Terminal {
 
   /// Outputs a string over the cursor and outputs a break / newline.
   pub fn outln(&self, s: String) {
-    self.win.printw(s);
+    self.backend.out(&s);
     self.outbr();
   }
 
   /// Outputs a newline.
   pub fn outbr(&self) {
-    self.win.printw(String::from("\n"));
+    self.backend.out("\n");
     self.refresh();
   }
 
   /// Outputs a string without refreshing the terminal.
   pub fn raw_out(&self, s: String) {
-    self.win.printw(s);
+    self.backend.out(&s);
   }
 
   /// Outputs a string that does not affect the cursor position and does not refresh the terminal.
@@ -345,33 +968,33 @@ impl Terminal {
 
   /// Outputs a string and a break / newline without refreshing the terminal.
   pub fn raw_outln(&self, s: String) {
-    self.win.printw(s);
+    self.backend.out(&s);
     self.raw_br();
   }
 
   /// Outputs a newline without refreshing the terminal.
   pub fn raw_br(&self) {
-    self.win.printw(String::from("\n"));
+    self.backend.out("\n");
   }
 
   /// Returns a tuple containing the current cursor position in the form (x, y)
   pub fn raw_posxy(&self) -> (i32, i32) {
-    (self.win.get_cur_x(), self.win.get_cur_y())
+    self.backend.posxy()
   }
 
   /// Returns the x position of the current cursor position.
   pub fn raw_posx(&self) -> i32 {
-    self.win.get_cur_x()
+    self.raw_posxy().0
   }
 
   /// Returns the y position of the current cursor position.
   pub fn raw_posy(&self) -> i32 {
-    self.win.get_cur_y()
+    self.raw_posxy().1
   }
 
   /// Moves the cursor to position x and y.
   pub fn raw_move(&self, x: i32, y: i32) {
-    self.win.mv(y, x);
+    self.backend.move_to(x, y);
   }
 
   /// Offsets the cursor by x and y.
@@ -396,7 +1019,7 @@ impl Terminal {
 
   /// Deletes the character that the current cursor is on.
   pub fn raw_delete(&self) {
-    self.win.delch();
+    self.backend.delete();
   }
 
   /// Deletes the last character (not letter).
@@ -408,24 +1031,27 @@ impl Terminal {
   /// Deletes all the characters from the cursor until the current position is offset position.
   /// 
   /// # Examples
-  /// ```
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
   /// t.out("Hello world!".into());
   /// t.raw_delete_offset(-6);
   /// ```
   /// Output: `Hello `
+  /// 
+  /// An offset of zero deletes nothing.
   pub fn raw_delete_offset(&self, xoffs: i32) {
-    let offs = xoffs / xoffs.abs(); // -1 or 1
-    let here = self.raw_posx();
-    while self.raw_posx() != here + xoffs {
-      self.raw_move_offset(offs, 0);
-      self.raw_delete();
+    if xoffs != 0 {
+      self.raw_delete_to(self.raw_posx() + xoffs);
     }
   }
 
   /// Deletes, from the start of the line, all the characters until `chars` position.
   /// 
   /// # Examples
-  /// ```
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
   /// t.out("Hello world!".into());
   /// t.raw_delete_from(5);
   /// ```
@@ -439,7 +1065,9 @@ impl Terminal {
   /// Deletes all characters from the cursor until the x position is `x`
   /// 
   /// # Examples
-  /// ```
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
   /// t.out("Hello world!".into());
   /// t.raw_delete_to(5);
   /// ```
@@ -452,116 +1080,688 @@ impl Terminal {
     };
 
     while self.raw_posx() != x {
+      let last = self.raw_posx();
       self.raw_move_offset(offs, 0);
+      if self.raw_posx() == last {
+        break; // the cursor cannot move any further, such as past the edge of the screen
+      }
       self.raw_delete();
     }
   }
 
-  /// Gets a character from input.
-  pub fn get_char(&self) -> Option<Key> {
-    match self.win.getch() {
-      Some(Input::Character('\n')) | Some(Input::Character('\r')) => Some(Key::Enter),
-      Some(Input::Character('\x08')) => Some(Key::Backspace),
-      Some(Input::Character(c)) => Some(Key::Alpha(c)),
-      Some(Input::KeyUp) => Some(Key::ArrowUp),
-      Some(Input::KeyDown) => Some(Key::ArrowDown),
-      Some(Input::KeyLeft) => Some(Key::ArrowLeft),
-      Some(Input::KeyRight) => Some(Key::ArrowRight),
-      Some(Input::KeyF1) => Some(Key::F1),
-      Some(Input::KeyF2) => Some(Key::F2),
-      Some(Input::KeyF3) => Some(Key::F3),
-      Some(Input::KeyF4) => Some(Key::F4),
-      Some(Input::KeyF5) => Some(Key::F5),
-      Some(Input::KeyF6) => Some(Key::F6),
-      Some(Input::KeyF7) => Some(Key::F7),
-      Some(Input::KeyF8) => Some(Key::F8),
-      Some(Input::KeyF9) => Some(Key::F9),
-      Some(Input::KeyF10) => Some(Key::F10),
-      Some(Input::KeyF11) => Some(Key::F11),
-      Some(Input::KeyF12) => Some(Key::F12),
-      None => None,
-      _ => None,
+  /// Waits for a key or another event, without echoing keys.
+  /// 
+  /// Events are read from the input source set with `set_input`, or from the backend if there is none.
+  /// When the terminal is resized every layer is re-drawn for the new size before the resize event is returned,
+  /// repositioning anchored layer2Ds and clearing stale cells.
+  /// 
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::VirtualBackend;
+  /// # use utile_cli::cli::*;
+  /// let t = Terminal::with_backend(VirtualBackend::new(20, 5));
+  /// t.backend().resize(30, 10);
+  /// assert_eq!(t.get_event(), Some(Event::Resize(30, 10)));
+  /// assert_eq!(t.size(), (30, 10));
+  /// ```
+  pub fn get_event(&self) -> Option<Event> {
+    let event = match self.input.borrow_mut().as_mut() {
+      Some(input) => input.read_event(),
+      None => self.backend.get_event()
+    };
+    if let Some(Event::Resize(..)) = event {
+      self.redraw();
     }
+    event
+  }
+
+  /// Waits up to `timeout` for an event like `get_event`, returning `Ok(None)` if none arrived in time,
+  /// or `Error::InputEnded` if input ended.
+  /// 
+  /// # Examples
+  /// Updating a spinner until a key is pressed:
+  /// ```no_run
+  /// # use std::time::Duration;
+  /// # use utile_cli::cli::*;
+  /// # let mut t = Terminal::new();
+  /// let mut spinner = Layer::new(0, 0);
+  /// for frame in "|/-\\".chars().cycle() {
+  ///   spinner.set_content(frame.to_string());
+  ///   t.draw_layer(&spinner);
+  ///   if let Ok(Some(Event::Key(_))) | Err(_) = t.get_event_timeout(Duration::from_millis(100)) {
+  ///     break;
+  ///   }
+  /// }
+  /// ```
+  pub fn get_event_timeout(&self, timeout: Duration) -> Result<Option<Event>> {
+    let event = match self.input.borrow_mut().as_mut() {
+      Some(input) => input.read_event_timeout(timeout),
+      None => self.backend.get_event_timeout(timeout)
+    }?;
+    if let Some(Event::Resize(..)) = event {
+      self.redraw();
+    }
+    Ok(event)
+  }
+
+  /// Gets a character from input, echoing it over the cursor unless echo was turned off with `TerminalBuilder::echo`.
+  pub fn get_char(&self) -> Option<Key> {
+    let ret = self.get_char_hidden();
+    self.echo_key(ret.as_ref());
+    ret
   }
 
   /// Returns a character however hides it from input.
+  /// 
+  /// Other events are handled and skipped, see `get_event`.
   pub fn get_char_hidden(&self) -> Option<Key> {
-    let here = self.raw_posxy();
-    let ret = self.get_char();
-    match ret {
-      Some(Key::Alpha(c)) => { self.raw_delete_prev(); },
-      _ | None => ()
+    loop {
+      if let Event::Key(key) = self.get_event()? {
+        return Some(key);
+      }
     }
-    self.raw_move(here.0, here.1);
-    ret
   }
 
-  /// Asks the user for input, prefixing the question with `prefix`
+  /// Waits up to `timeout` for a character like `get_char`, returning `Ok(None)` if no key was pressed in time,
+  /// or `Error::InputEnded` if input ended.
   /// 
   /// # Examples
+  /// ```no_run
+  /// # use std::time::Duration;
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
+  /// match t.get_char_timeout(Duration::from_secs(5)) {
+  ///   Ok(Some(key)) => t.outln(format!("pressed {:?}", key)),
+  ///   Ok(None) => t.outln("too slow".into()),
+  ///   Err(_) => ()
+  /// }
   /// ```
-  /// t.ask("> ".into())
-  /// ```
-  pub fn ask(&self, prefix: String) -> String {
-    self.out(prefix);
-    let mut r = Layer::new(self.raw_posx(), self.raw_posy());
-    while let Some(i) = self.get_char_hidden() {
-      match i {
-        Key::Enter => break,
-        Key::Backspace => {
-          if let Some(i) = r.get_content().pop() {
-            let mut content = r.get_content();
-            content.pop();
-            r.set_content(content);
-            self.draw_layer(&r);
-          } else {
-            self.raw_move_next();
-            continue;
+  pub fn get_char_timeout(&self, timeout: Duration) -> Result<Option<Key>> {
+    let ret = self.get_char_timeout_hidden(timeout)?;
+    self.echo_key(ret.as_ref());
+    Ok(ret)
+  }
+
+  /// Returns a key if one was already pressed without waiting, like `get_char_timeout` with no timeout however without echoing it.
+  pub fn poll_key(&self) -> Result<Option<Key>> {
+    self.get_char_timeout_hidden(Duration::from_secs(0))
+  }
+
+  // waits for a key, handling and skipping other events without extending the timeout
+  fn get_char_timeout_hidden(&self, timeout: Duration) -> Result<Option<Key>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+      match self.get_event_timeout(deadline.saturating_duration_since(Instant::now()))? {
+        Some(Event::Key(key)) => return Ok(Some(key)),
+        Some(_) => continue,
+        None => return Ok(None)
+      }
+    }
+  }
+
+  // echoes a printable key unless echo is off
+  fn echo_key(&self, key: Option<&Key>) {
+    if let (true, Some(c)) = (self.echo, key.and_then(Key::printable)) {
+      self.out(c.to_string());
+    }
+  }
+
+  // event for prompts, where the end of input and timing out are errors
+  fn next_event(&self) -> Result<Event> {
+    match self.prompt_timeout.get() {
+      Some(timeout) => self.get_event_timeout(timeout)?.ok_or(Error::TimedOut),
+      // without waiting, no key pressed yet is a timeout rather than the end of input
+      None if self.nodelay => self.get_event_timeout(Duration::from_secs(0))?.ok_or(Error::TimedOut),
+      None => self.get_event().ok_or(Error::InputEnded)
+    }
+  }
+
+  /// Runs `app` until it quits, drawing it and refreshing the layers once at the start and again whenever it asks to be redrawn.
+  /// 
+  /// Events are passed to `App::handle_event`, including resize events after the layers have been re-drawn for the new size,
+  /// and `Event::Tick` every time the app's tick rate passes.
+  /// The loop also stops once input ends, see `try_run`.
+  pub fn run<A: App<B>>(&mut self, app: &mut A) {
+    let _ = self.try_run(app);
+  }
+
+  /// Runs `app` like `run`, however returns `Error::InputEnded` if input ends before the app quits.
+  pub fn try_run<A: App<B>>(&mut self, app: &mut A) -> Result<()> {
+    app.draw(self);
+    self.refresh();
+    let mut tick = app.tick_rate().map(|rate| Instant::now() + rate);
+    loop {
+      let event = match tick {
+        Some(at) => match self.get_event_timeout(at.saturating_duration_since(Instant::now()))? {
+          Some(event) => event,
+          None => {
+            // a slow app skips ticks instead of sending them back to back
+            let now = Instant::now();
+            tick = app.tick_rate().map(|rate| (at + rate).max(now));
+            Event::Tick
           }
         },
-        Key::Alpha(c) => {
-          let mut content = r.get_content();
-          content.push(c);
-          r.set_content(content);
-          self.draw_layer(&r);
+        None => self.get_event().ok_or(Error::InputEnded)?
+      };
+      match app.handle_event(event) {
+        Control::Continue => (),
+        Control::Redraw => {
+          app.draw(self);
+          self.refresh();
         },
-        _ => continue,
+        Control::Quit => return Ok(())
+      }
+      if tick.is_none() {
+        tick = app.tick_rate().map(|rate| Instant::now() + rate);
       }
     }
-    r.get_content()
+  }
+
+  /// Asks the user for input, prefixing the question with `prefix`
+  /// 
+  /// The answer can be edited with the cursor keys and the usual shell shortcuts, see `LineEditor`.
+  /// If the terminal has a history, answers are added to it and can be recalled with ArrowUp or searched with Ctrl-R, see `History`.
+  /// Pasted text is inserted with its newlines as spaces, leaving out those at the end and other control characters besides tabs.
+  /// 
+  /// # Examples
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
+  /// t.ask("> ".into());
+  /// ```
+  pub fn ask(&self, prefix: String) -> String {
+    self.try_ask(prefix).unwrap_or_default()
+  }
+
+  /// Asks the user for input like `ask`, however returns `Error::Cancelled` if the prompt is cancelled,
+  /// `Error::TimedOut` if the prompt timeout passes without a key, or `Error::InputEnded` if input ends before it is submitted.
+  pub fn try_ask(&self, prefix: String) -> Result<String> {
+    self.try_ask_with(prefix, &AskOptions::new())
+  }
+
+  /// Asks the user for input like `ask`, with options such as a default answer, tab completion or validation.
+  /// 
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::VirtualBackend;
+  /// # use utile_cli::cli::*;
+  /// # use utile_cli::complete::WordCompleter;
+  /// # use utile_cli::prompt::AskOptions;
+  /// let t = Terminal::with_backend(VirtualBackend::new(20, 5));
+  /// t.backend().feed_script("st<Tab>a<Tab>\n").unwrap();
+  /// let options = AskOptions::new().completer(WordCompleter::new(vec!["start", "stop"]));
+  /// assert_eq!(t.ask_with("> ".into(), &options), "start");
+  /// ```
+  pub fn ask_with(&self, prefix: String, options: &AskOptions) -> String {
+    self.try_ask_with(prefix, options).unwrap_or_default()
+  }
+
+  /// Asks the user for input like `ask_with`, returning the same errors as `try_ask`.
+  pub fn try_ask_with(&self, prefix: String, options: &AskOptions) -> Result<String> {
+    self.out(prefix);
+    self.edit_line(options, None, &|s| Ok(s.to_string()))
+  }
+
+  /// Asks the user for input like `ask`, parsing the answer into a `T`, or returning `T::default()` if the prompt fails.
+  /// 
+  /// An answer which does not parse stays in the prompt, with the parse error shown on the line below until it is edited.
+  /// 
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::VirtualBackend;
+  /// # use utile_cli::cli::*;
+  /// let t = Terminal::with_backend(VirtualBackend::new(40, 5));
+  /// t.backend().feed_script("http\n<C-u>8080\n").unwrap();
+  /// let port: u16 = t.ask_parse("port: ".into());
+  /// assert_eq!(port, 8080);
+  /// ```
+  pub fn ask_parse<T: FromStr + Default>(&self, prefix: String) -> T where T::Err: fmt::Display {
+    self.try_ask_parse(prefix).unwrap_or_default()
+  }
+
+  /// Asks the user for input like `ask_parse`, returning the same errors as `try_ask`.
+  pub fn try_ask_parse<T: FromStr>(&self, prefix: String) -> Result<T> where T::Err: fmt::Display {
+    self.out(prefix);
+    self.edit_line(&AskOptions::new(), None, &|s| s.parse().map_err(|e: T::Err| e.to_string()))
+  }
+
+  /// Asks the user for a number within the bounds of `options`,
+  /// or returns zero (the bound nearest to it if zero is out of bounds) if the prompt fails.
+  /// 
+  /// ArrowUp and ArrowDown step the number up and down instead of walking through the history,
+  /// and an answer which is not a number or is out of bounds stays in the prompt with the error shown on the line below.
+  /// See `NumberOptions` for an example.
+  pub fn ask_number<T: Number>(&self, prefix: String, options: &NumberOptions<T>) -> T {
+    self.try_ask_number(prefix, options).unwrap_or_else(|_| options.start())
+  }
+
+  /// Asks the user for a number like `ask_number`, returning the same errors as `try_ask`.
+  pub fn try_ask_number<T: Number>(&self, prefix: String, options: &NumberOptions<T>) -> Result<T> {
+    let number = *options;
+    let ask = AskOptions { stepper: Some(Box::new(move |s, up| Some(number.step_from(s, up)?.to_string()))), ..AskOptions::new() };
+    self.out(prefix);
+    self.edit_line(&ask, None, &|s| {
+      let n = s.trim().parse().map_err(|_| "not a number".to_string())?;
+      number.check(n)
+    })
   }
 
   /// Asks the user for input, however the input is masked by a series of `mask` to hide the input.
+  /// 
+  /// The input can be edited like in `ask`. See `password` for a prompt made for passwords.
   pub fn mask(&self, prefix: String, mask: char) -> String {
+    self.try_mask(prefix, mask).unwrap_or_default()
+  }
+
+  /// Asks for masked input like `mask`, however returns `Error::Cancelled` if the prompt is cancelled,
+  /// `Error::TimedOut` if the prompt timeout passes without a key, or `Error::InputEnded` if input ends before it is submitted.
+  pub fn try_mask(&self, prefix: String, mask: char) -> Result<String> {
+    self.out(prefix);
+    self.edit_line(&AskOptions::new(), Some(mask), &|s| Ok(s.to_string()))
+  }
+
+  /// Asks the user for a password, masking it and returning it as a `Secret` which is zeroed once dropped,
+  /// or an empty secret if the prompt fails.
+  /// 
+  /// The password can be edited like in `ask`, and shown or masked again with the reveal key of the options,
+  /// being masked again once submitted.
+  /// A password failing the checks of the options stays in the prompt with the reason shown below it,
+  /// and with a confirmation, the password has to be typed again to match. See `PasswordOptions` for an example.
+  pub fn password(&self, prefix: String, options: &PasswordOptions) -> Secret {
+    self.try_password(prefix, options).unwrap_or_default()
+  }
+
+  /// Asks for a password like `password`, however returns `Error::Cancelled` if the prompt is cancelled,
+  /// `Error::TimedOut` if the prompt timeout passes without a key, or `Error::InputEnded` if input ends before it is submitted.
+  pub fn try_password(&self, prefix: String, options: &PasswordOptions) -> Result<Secret> {
+    let ask = AskOptions { reveal: options.reveal.clone(), ..AskOptions::new() };
     self.out(prefix);
-    let mut r = Layer::new(self.raw_posx(), self.raw_posy());
-    let mut s = String::new();
-    while let Some(i) = self.get_char_hidden() {
-      match i {
-        Key::Enter => break,
-        Key::Backspace => {
-          if let Some(i) = r.get_content().pop() {
-            let mut content = r.get_content();
-            content.pop();
-            r.set_content(content);
-            self.draw_layer(&r);
-            s.pop();
-          } else {
-            self.raw_move_next();
-            continue;
+    let password = self.edit_line(&ask, Some(options.mask), &|s| {
+      if width::graphemes(s).count() < options.min_len {
+        return Err(format!("must be at least {} characters", options.min_len));
+      }
+      if let Some(validate) = &options.validator {
+        validate(s)?;
+      }
+      Ok(Secret::new(s.to_string()))
+    })?;
+    if let Some(prefix) = &options.confirm {
+      self.outbr();
+      self.out(prefix.clone());
+      self.edit_line(&ask, Some(options.mask), &|s| match s == password.expose() {
+        true => Ok(()),
+        false => Err("does not match".to_string())
+      })?;
+    }
+    Ok(password)
+  }
+
+  /// Asks the user for several lines of text, prefixing the first with `prefix`, and returns them joined by '\n'.
+  /// 
+  /// The text is edited in a viewport of `TextAreaOptions::height` lines from the cursor down, which scrolls to follow the cursor,
+  /// see `TextEditor` for the keys. Near the bottom of a screen which does not scroll, such as a `VirtualBackend`,
+  /// the viewport only has the rows left below the cursor. Lines longer than the screen is wide are cut off.
+  /// As Enter starts a new line, the text is submitted with the submit key of the options, Ctrl-D by default.
+  /// 
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::VirtualBackend;
+  /// # use utile_cli::cli::*;
+  /// # use utile_cli::prompt::TextAreaOptions;
+  /// let t = Terminal::with_backend(VirtualBackend::new(20, 5));
+  /// t.backend().feed_script("Fix typo\n\nIn the README.<C-d>").unwrap();
+  /// let message = t.text_area("> ".into(), &TextAreaOptions::new().height(2));
+  /// assert_eq!(message, "Fix typo\n\nIn the README.");
+  /// assert_eq!(t.backend().contents(), ">\n  In the README.");
+  /// ```
+  pub fn text_area(&self, prefix: String, options: &TextAreaOptions) -> String {
+    self.try_text_area(prefix, options).unwrap_or_default()
+  }
+
+  /// Asks for several lines of text like `text_area`, however returns `Error::Cancelled` if the prompt is cancelled,
+  /// `Error::TimedOut` if the prompt timeout passes without a key, or `Error::InputEnded` if input ends before it is submitted.
+  pub fn try_text_area(&self, prefix: String, options: &TextAreaOptions) -> Result<String> {
+    self.out(prefix);
+    let (x, top) = self.raw_posxy();
+    // claims the rows below for the viewport, which scrolls terminals that scroll,
+    // while on screens that do not the viewport shrinks to the rows left at the bottom
+    for _ in 1..options.height.min(self.size().1.max(1) as usize) {
+      let y = self.raw_posy();
+      self.raw_out("\n".into());
+      if self.raw_posy() == y {
+        break;
+      }
+    }
+    let height = (self.raw_posy() - top + 1).max(1) as usize;
+    let mut rows: Vec<Layer> = (0..height).map(|i| Layer::new(x, top + i as i32)).collect();
+    let mut text = TextEditor::with_text(&options.initial);
+    let mut scroll = 0;
+    self.draw_text_area(&mut rows, &text, &mut scroll);
+    loop {
+      let key = match self.next_event()? {
+        Event::Key(key) => key,
+        Event::Paste(pasted) => {
+          text.insert(&pasted.chars().filter(|&c| c == '\n' || !c.is_control()).collect::<String>());
+          self.draw_text_area(&mut rows, &text, &mut scroll);
+          continue;
+        },
+        _ => continue
+      };
+      if key == options.submit {
+        break;
+      }
+      if let Some(Action::Cancel) = self.keymap.action(&key) {
+        return Err(Error::Cancelled);
+      }
+      if text.handle_key(&key) {
+        self.draw_text_area(&mut rows, &text, &mut scroll);
+      }
+    }
+    text.end();
+    self.draw_text_area(&mut rows, &text, &mut scroll);
+    Ok(text.text())
+  }
+
+  // edits a line at the cursor until it is submitted, showing every grapheme cluster as `mask` if there is one
+  // and parsing the answer with `parse`, which rejects it like the validator of the options does
+  // masked lines and lines stepped with the arrow keys are not added to the history
+  fn edit_line<T>(&self, options: &AskOptions, hide: Option<char>, parse: &dyn Fn(&str) -> std::result::Result<T, String>) -> Result<T> {
+    let use_history = hide.is_none() && options.stepper.is_none() && self.history.borrow().is_some();
+    // none while a masked line is revealed
+    let mut mask = hide;
+    if let Some(default) = &options.default {
+      self.out(format!("(default: {}) ", default));
+    }
+    let mut r = Wiped(Layer::new(self.raw_posx(), self.raw_posy()));
+    let mut line = LineEditor::with_text(&options.initial);
+    // the placeholder is shown until the first key
+    let mut hint = false;
+    match &options.placeholder {
+      Some(placeholder) if line.text().is_empty() => {
+        r.set_content(placeholder.clone());
+        self.backend.out_dim(placeholder);
+        self.raw_move(r.posx, r.posy);
+        hint = true;
+      },
+      _ => self.draw_line(&mut r, &line, mask)
+    }
+    // the entry being shown, and the line being typed before walking through the history
+    let mut entry: Option<usize> = None;
+    let mut draft = String::new();
+    // a key which ended a search, to be handled like any other
+    let mut pending: Option<Key> = None;
+    // the candidates listed by a second Tab, until the next key
+    let mut popup: Option<Layer2D> = None;
+    let mut tabbed = false;
+    // the error of a rejected answer, until the answer is edited
+    // copies of the line are zeroed once dropped, as it may be a password
+    let mut rejected: Option<(Layer, Zeroizing<String>)> = None;
+    let answer = loop {
+      if rejected.as_ref().is_some_and(|(_, text)| **text != line.text()) {
+        if let Some((mut error, _)) = rejected.take() {
+          error.set_content(String::new());
+          self.draw_layer_static(&error);
+          // an error after the line may have covered what was typed since
+          if error.posy == r.posy {
+            self.draw_line(&mut r, &line, mask);
           }
+        }
+      }
+      let event = match pending.take() {
+        Some(key) => Event::Key(key),
+        None => match self.next_event()? {
+          event @ Event::Key(_) | event @ Event::Paste(_) => event,
+          _ => continue
+        }
+      };
+      if let Some(popup) = popup.take() {
+        self.clear_layer2d(popup);
+      }
+      if std::mem::replace(&mut hint, false) {
+        self.draw_line(&mut r, &line, mask);
+      }
+      let key = match event {
+        Event::Key(key) => key,
+        Event::Paste(text) => {
+          line.insert(&pasted(&text).collect::<String>());
+          self.draw_line(&mut r, &line, mask);
+          continue;
         },
-        Key::Alpha(c) => {
-          let mut content = r.get_content();
-          content.push(mask);
-          s.push(c);
-          r.set_content(content);
-          self.draw_layer(&r);
+        _ => continue
+      };
+      let tab = std::mem::replace(&mut tabbed, key == Key::Tab);
+      match self.keymap.action(&key) {
+        Some(Action::Submit) => {
+          let text = Zeroizing::new(match &options.default {
+            Some(default) if line.text().is_empty() => default.clone(),
+            _ => line.text().to_string()
+          });
+          let valid = options.validator.as_ref().map_or(Ok(()), |validate| validate(&text));
+          match valid.and_then(|_| parse(&text)) {
+            Ok(answer) => {
+              // a revealed password is masked again once answered
+              line.set_text(&text);
+              self.draw_line(&mut r, &line, hide);
+              break answer;
+            },
+            Err(message) => {
+              if rejected.is_none() {
+                rejected = Some((self.show_error(&r, message), Zeroizing::new(line.text().to_string())));
+              }
+              continue;
+            }
+          }
         },
-        _ => continue,
+        Some(Action::Cancel) => return Err(Error::Cancelled),
+        _ => ()
+      }
+      if hide.is_some() && options.reveal.as_ref() == Some(&key) {
+        mask = if mask.is_some() { None } else { hide };
+        self.draw_line(&mut r, &line, mask);
+        continue;
+      }
+      if let (Key::Tab, Some(completer)) = (&key, &options.completer) {
+        let (start, candidates) = completer.complete(line.text(), line.cursor());
+        let start = start.min(line.cursor());
+        // the start comes from the completer, which may not have split the line between characters
+        if !line.text().is_char_boundary(start) {
+          continue;
+        }
+        let typed = &line.text()[start..line.cursor()];
+        let common = common_prefix(&candidates);
+        if common.len() > typed.len() && common.starts_with(typed) || candidates.len() == 1 && common != typed {
+          line.complete(start, common);
+          self.draw_line(&mut r, &line, mask);
+        } else if tab && candidates.len() > 1 {
+          popup = self.completion_popup(r.posy + 1, &candidates);
+        }
+        continue;
+      }
+      if let Some(step) = &options.stepper {
+        let up = key == Key::ArrowUp;
+        if up || key == Key::ArrowDown {
+          if let Some(text) = step(line.text(), up) {
+            line.set_text(&text);
+            self.draw_line(&mut r, &line, mask);
+          }
+          continue;
+        }
+      }
+      if use_history {
+        let len = self.history.borrow().as_ref().map_or(0, History::len);
+        let older = key == Key::ArrowUp || key == Key::ctrl('p');
+        let newer = key == Key::ArrowDown || key == Key::ctrl('n');
+        if older || newer {
+          let next = match entry {
+            None if older && len > 0 => {
+              draft = line.text().to_string();
+              Some(len - 1)
+            },
+            Some(i) if older => Some(i.saturating_sub(1)),
+            Some(i) if newer && i + 1 < len => Some(i + 1),
+            _ => None
+          };
+          if next.is_some() || entry.is_some() {
+            let history = self.history.borrow();
+            line.set_text(next.and_then(|i| history.as_ref()?.get(i)).unwrap_or(&draft));
+            drop(history);
+            entry = next;
+            self.draw_line(&mut r, &line, mask);
+          }
+          continue;
+        }
+        if key == Key::ctrl('r') {
+          pending = self.search_history(&mut r, &mut line)?;
+          entry = None;
+          self.draw_line(&mut r, &line, mask);
+          continue;
+        }
+      }
+      if line.handle_key(&key) {
+        self.draw_line(&mut r, &line, mask);
       }
+    };
+    if use_history {
+      if let Some(history) = self.history.borrow_mut().as_mut() {
+        // prompts do not fail once answered, `History::save` reports write errors
+        let _ = history.push(line.text());
+      }
+    }
+    Ok(answer)
+  }
+
+  // lists completion candidates in columns from row `y` down, as far as the screen goes
+  fn completion_popup(&self, y: i32, candidates: &[String]) -> Option<Layer2D> {
+    let width = self.size().0;
+    let column = candidates.iter().map(|c| width::width(c)).max()? + 2;
+    let columns = (width.max(0) as usize / column).max(1);
+    let rows = candidates.len().div_ceil(columns).min((self.visible_rows().end - y).max(0) as usize);
+    if rows == 0 {
+      return None;
+    }
+    let mut blank = Layer::new(0, 0);
+    blank.set_content(" ".repeat(column));
+    let mut popup = Layer2D::new(0, y, columns, rows, blank);
+    for (i, l) in popup.layers.iter_mut().enumerate() {
+      l.set_content(candidates.get(i).cloned().unwrap_or_default());
     }
-    s
+    self.draw_layer2d(&popup);
+    Some(popup)
+  }
+
+  // draws an error on the row below the line `r` without moving the cursor, scrolling a backend whose output scrolls to reach it,
+  // or after the line on its own row if that is the last row of the screen
+  fn show_error(&self, r: &Layer, message: String) -> Layer {
+    let below = r.posy + 1;
+    if !self.visible_rows().contains(&below) {
+      let here = self.raw_posxy();
+      self.raw_move(0, below);
+      self.raw_move(here.0, here.1);
+    }
+    let mut error = match self.visible_rows().contains(&below) {
+      true => Layer::new(0, below),
+      false => Layer::new(r.posx + width::width(&r.get_content()) as i32 + 1, r.posy)
+    };
+    error.set_content(message);
+    self.draw_layer_static(&error);
+    error
+  }
+
+  // blanks the cells of a layer2D drawn with `draw_layer2d`
+  fn clear_layer2d(&self, mut layer: Layer2D) {
+    layer.layers.iter_mut().for_each(|l| { l.set_content(String::new()); });
+    self.draw_layer2d(&layer);
+  }
+
+  // searches the history backwards as the user types, returning the key which accepted the match if it should be handled
+  fn search_history(&self, r: &mut Layer, line: &mut LineEditor) -> Result<Option<Key>> {
+    let history = match self.history.borrow().clone() {
+      Some(history) => history,
+      None => return Ok(None)
+    };
+    let mut query = String::new();
+    let mut found: Option<usize> = None;
+    let mut failed = false;
+    loop {
+      let shown = found.and_then(|i| history.get(i)).unwrap_or("");
+      let label = if failed { "failed reverse-i-search" } else { "reverse-i-search" };
+      r.set_content(format!("({})`{}': {}", label, query, shown));
+      self.draw_layer(r);
+      let before = match self.next_event()? {
+        Event::Key(Key::Backspace) => {
+          query.pop();
+          history.len()
+        },
+        Event::Key(key) if key == Key::ctrl('r') => found.unwrap_or(history.len()),
+        Event::Key(key) if key.printable().is_some() => {
+          query.extend(key.printable());
+          found.map_or(history.len(), |i| i + 1)
+        },
+        Event::Paste(text) => {
+          query.extend(pasted(&text));
+          found.map_or(history.len(), |i| i + 1)
+        },
+        Event::Key(key) => {
+          if key == Key::ctrl('g') || self.keymap.action(&key) == Some(Action::Cancel) {
+            return Ok(None);
+          }
+          if let Some(i) = found {
+            line.set_text(history.get(i).unwrap_or(""));
+          }
+          return Ok(Some(key));
+        },
+        _ => continue
+      };
+      match history.search(&query, before) {
+        Some(i) => {
+          found = Some(i);
+          failed = false;
+        },
+        None => failed = true
+      }
+    }
+  }
+
+  // draws an edited line to its layer, leaving the cursor at the line's cursor
+  fn draw_line(&self, r: &mut Layer, line: &LineEditor, mask: Option<char>) {
+    // the line may be a revealed password, which is not left behind in the old content
+    r.wipe();
+    let cursor = match mask {
+      Some(mask) => {
+        let graphemes = width::graphemes(line.text()).count();
+        r.set_content(mask.to_string().repeat(graphemes));
+        line.cursor_graphemes() * width::width(&mask.to_string())
+      },
+      None => {
+        r.set_content(line.text().to_string());
+        line.cursor_width()
+      }
+    };
+    self.draw_layer(r);
+    self.raw_move(r.posx + cursor as i32, r.posy);
+  }
+
+  // draws the lines of a text area which fit in its rows, scrolled to show the cursor, and moves the cursor to its place
+  fn draw_text_area(&self, rows: &mut [Layer], text: &TextEditor, scroll: &mut usize) {
+    let height = rows.len();
+    if text.row() < *scroll {
+      *scroll = text.row();
+    } else if text.row() >= *scroll + height {
+      *scroll = text.row() + 1 - height;
+    }
+    let room = (self.size().0 - rows[0].posx).max(0) as usize;
+    let mut lines = text.lines().skip(*scroll);
+    for row in rows.iter_mut() {
+      let mut used = 0;
+      let shown = width::graphemes(lines.next().unwrap_or("")).take_while(|(_, w)| {
+        used += w;
+        used <= room
+      }).map(|(g, _)| g).collect();
+      row.set_content(shown);
+      self.draw_layer(row);
+    }
+    let x = text.line().cursor_width().min(room.saturating_sub(1));
+    self.raw_move(rows[0].posx + x as i32, rows[0].posy + (text.row() - *scroll) as i32);
   }
 
   /// Asks a y/n question to the user, returning a boolean (true if yes).
@@ -569,16 +1769,33 @@ impl Terminal {
   /// The `suffix` parameter must be specified like "exampley/examplen" (must contain a '/')
   /// The `default` parameter is the default highlighted y/n
   /// 
-  /// You can choose a yes or a no using the left and right arrow keys.
+  /// You can choose a yes or a no using the left and right arrow keys, or answer by clicking one.
+  /// The keys are looked up in the terminal's keymap, where `Prev` chooses yes, `Next` chooses no and `Toggle` switches between them.
   /// # Examples
-  /// ```
-  /// t.yesno("y/n".into(), true)
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
+  /// t.yesno("y/n".into(), true);
   /// ```
   /// Outputs: (Y/n)
+  /// 
+  /// # Panics
+  /// Panics if `suffix` does not contain a '/', see `try_yesno`.
   pub fn yesno(&self, suffix: String, default: bool) -> bool {
-    let yn: Vec<String> = suffix.split('/').map(|s| String::from(s)).collect();
+    match self.try_yesno(suffix, default) {
+      Ok(r) => r,
+      Err(Error::InputEnded) | Err(Error::Cancelled) | Err(Error::TimedOut) => default,
+      Err(e) => panic!("{}", e)
+    }
+  }
+
+  /// Asks a y/n question like `yesno`, however returns `Error::InvalidSuffix` if `suffix` does not contain a '/',
+  /// `Error::Cancelled` if the prompt is cancelled, `Error::TimedOut` if the prompt timeout passes without a key,
+  /// or `Error::InputEnded` if input ends before it is answered.
+  pub fn try_yesno(&self, suffix: String, default: bool) -> Result<bool> {
+    let yn: Vec<String> = suffix.split('/').map(String::from).collect();
     if yn.len() == 1 {
-      panic!("Expected a '/' character separating a yes no question!");
+      return Err(Error::InvalidSuffix(suffix));
     }
     let mut ynl = Layer::new(self.raw_posx(), self.raw_posy());
     let mut y = yn[0].clone();
@@ -590,33 +1807,71 @@ impl Terminal {
     ynl.set_content(format!("({}/{})", y.clone(), n.clone()));
     self.draw_layer(&ynl);
     let mut ret = default;
-    while let Some(i) = self.get_char_hidden() {
-      match i {
-        Key::Enter => break,
-        Key::ArrowRight => { ret = false; },
-        Key::ArrowLeft => { ret = true; },
+    loop {
+      let clicked = match self.next_event()? {
+        Event::Key(key) => match self.keymap.action(&key) {
+          Some(Action::Submit) => break,
+          Some(Action::Cancel) => return Err(Error::Cancelled),
+          Some(Action::Next) => { ret = false; false },
+          Some(Action::Prev) => { ret = true; false },
+          Some(Action::Toggle) => { ret = !ret; false },
+          None => continue
+        },
+        // clicking an option answers with it, the content being "(y/n)"
+        Event::Mouse(m) if m.is_click() && ynl.contains(m.x, m.y) => {
+          let slash = ynl.posx + 1 + width::width(&y) as i32;
+          match m.x {
+            x if x > ynl.posx && x < slash => { ret = true; },
+            x if x > slash && x <= slash + width::width(&n) as i32 => { ret = false; },
+            _ => continue
+          }
+          true
+        },
         _ => continue
-      }
+      };
       match ret {
         true => { y = { n = n.to_lowercase(); y.to_uppercase() } },
         false => { n = { y = y.to_lowercase(); n.to_uppercase() } },
       }
       ynl.set_content(format!("({}/{})", y.clone(), n.clone()));
       self.draw_layer(&ynl);
+      if clicked {
+        break;
+      }
     }
-    ret
+    Ok(ret)
   }
 
   /// Gives the user choices between strings.
   /// Takes in a `prefix` to be added as a prefix on the current choice, and vector `strs` as the choices.
-  /// The result output is a list in which options can be highlighted by using the arrow keys.
+  /// The result output is a list in which options can be highlighted by using the arrow keys, or picked by clicking them.
+  /// The keys are looked up in the terminal's keymap, where `Prev` and `Next` move through the choices.
   /// # Examples
-  /// ```
+  /// ```no_run
+  /// # use utile_cli::cli::*;
+  /// # let t = Terminal::new();
   /// let x = t.choices("-> ".into(), vec!["c1".into(), "c22".into(), "c333".into(), "c4444".into()]);
   /// t.outln(x);
   /// ```
   /// Output if selected was `c2`: `c2`
+  /// 
+  /// # Panics
+  /// Panics if `strs` is empty, see `try_choices`.
   pub fn choices(&self, prefix: String, strs: Vec<String>) -> String {
+    match self.try_choices(prefix, strs) {
+      Ok(r) => r,
+      Err(Error::InputEnded) | Err(Error::Cancelled) | Err(Error::TimedOut) => String::new(),
+      Err(e) => panic!("{}", e)
+    }
+  }
+
+  /// Gives the user choices like `choices`, however returns `Error::NoChoices` if `strs` is empty,
+  /// `Error::Cancelled` if the prompt is cancelled, `Error::TimedOut` if the prompt timeout passes without a key,
+  /// or `Error::InputEnded` if input ends before a choice is made.
+  pub fn try_choices(&self, prefix: String, strs: Vec<String>) -> Result<String> {
+    if strs.is_empty() {
+      return Err(Error::NoChoices);
+    }
     self.outbr();
     let mut layers: Vec<Layer> = vec![];
     for (i , str) in strs.iter().enumerate() {
@@ -631,29 +1886,41 @@ impl Terminal {
 
     self.raw_move_offset(0, layers.len() as i32);
     let mut y = 0;
-    while let Some(i) = self.get_char_hidden() {
-      match i {
-        Key::Enter => break,
-        Key::ArrowDown => {
-          if (y < layers.len() - 1) {
-            y += 1;
-          }
+    loop {
+      let clicked = match self.next_event()? {
+        Event::Key(key) => match self.keymap.action(&key) {
+          Some(Action::Submit) => break,
+          Some(Action::Cancel) => return Err(Error::Cancelled),
+          Some(Action::Next) => {
+            if y + 1 < layers.len() {
+              y += 1;
+            }
+            false
+          },
+          Some(Action::Prev) => {
+            y = y.saturating_sub(1);
+            false
+          },
+          _ => continue
+        },
+        // clicking a choice picks it
+        Event::Mouse(m) if m.is_click() => match layers.iter().position(|l| l.contains(m.x, m.y)) {
+          Some(i) => { y = i; true },
+          None => continue
         },
-        Key::ArrowUp => {
-          if (y > 0) {
-            y -= 1;
-          }
-        }
         _ => continue
-      }
+      };
       for (i, l) in layers.iter_mut().enumerate() {
         if i == y {
           l.set_content(format!("{}{}", prefix.clone(), l.inner_content.clone()));
         } else { l.inner_to_outer(); }
-        self.draw_layer_static(&l);
+        self.draw_layer_static(l);
+      }
+      if clicked {
+        break;
       }
     }
-    
-    layers[y].inner_content.clone()
+
+    Ok(layers[y].inner_content.clone())
   }
 }
\ No newline at end of file
@@ -0,0 +1,139 @@
+//! Tab completion for `Terminal::ask_with`.
+
+use std::path::PathBuf;
+
+/// A source of completions for a line being typed, see `AskOptions::completer`.
+///
+/// Closures taking the line and cursor are completers too.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::complete::Completer;
+/// let commands = |line: &str, pos: usize| {
+///   let candidates = ["help", "hello"].iter().filter(|c| c.starts_with(&line[..pos])).map(|c| c.to_string()).collect();
+///   (0, candidates)
+/// };
+/// assert_eq!(commands.complete("he", 2), (0, vec!["help".to_string(), "hello".to_string()]));
+/// ```
+pub trait Completer {
+  /// Returns the candidates for completing `line` with the cursor at byte index `pos`,
+  /// along with the byte index where the text they replace starts, which ends at the cursor.
+  ///
+  /// A start which is not on a character boundary of the line is ignored, along with the candidates.
+  fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>);
+}
+
+impl<F: Fn(&str, usize) -> (usize, Vec<String>)> Completer for F {
+  fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+    self(line, pos)
+  }
+}
+
+/// Completes the word before the cursor from a fixed list of words.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::complete::{Completer, WordCompleter};
+/// let c = WordCompleter::new(vec!["status", "stash", "log"]);
+/// assert_eq!(c.complete("git st", 6), (4, vec!["status".to_string(), "stash".to_string()]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WordCompleter {
+  words: Vec<String>
+}
+
+impl WordCompleter {
+  /// Returns a completer over `words`, offering them in this order.
+  pub fn new<I: IntoIterator<Item = S>, S: Into<String>>(words: I) -> WordCompleter {
+    WordCompleter { words: words.into_iter().map(Into::into).collect() }
+  }
+}
+
+impl Completer for WordCompleter {
+  fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+    let start = word_start(line, pos);
+    let word = &line[start..pos];
+    let mut candidates: Vec<String> = vec![];
+    for w in self.words.iter().filter(|w| w.starts_with(word)) {
+      if !candidates.contains(w) {
+        candidates.push(w.clone());
+      }
+    }
+    (start, candidates)
+  }
+}
+
+/// Completes the word before the cursor as a file system path, adding a '/' after directories.
+///
+/// Hidden files are only offered once the name being completed starts with a '.'.
+///
+/// # Examples
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # use utile_cli::complete::PathCompleter;
+/// # use utile_cli::prompt::AskOptions;
+/// # let t = Terminal::new();
+/// let file = t.ask_with("open: ".into(), &AskOptions::new().completer(PathCompleter::new()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathCompleter {
+  base: Option<PathBuf>
+}
+
+impl PathCompleter {
+  /// Returns a completer for paths relative to the current directory.
+  pub fn new() -> PathCompleter {
+    PathCompleter { base: None }
+  }
+
+  /// Returns a completer for paths relative to `base`.
+  pub fn in_dir<P: Into<PathBuf>>(base: P) -> PathCompleter {
+    PathCompleter { base: Some(base.into()) }
+  }
+}
+
+impl Completer for PathCompleter {
+  fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+    let start = word_start(line, pos);
+    let word = &line[start..pos];
+    let (dir, name) = match word.rfind('/') {
+      Some(i) => word.split_at(i + 1),
+      None => ("", word)
+    };
+    let base = self.base.clone().unwrap_or_else(|| PathBuf::from("."));
+    let path = if dir.is_empty() { base } else { base.join(dir) };
+    let entries = match std::fs::read_dir(path) {
+      Ok(entries) => entries,
+      Err(_) => return (start, vec![])
+    };
+    let mut candidates: Vec<String> = entries.filter_map(|e| {
+      let e = e.ok()?;
+      let file = e.file_name().to_string_lossy().into_owned();
+      if !file.starts_with(name) || (file.starts_with('.') && !name.starts_with('.')) {
+        return None;
+      }
+      let slash = if e.path().is_dir() { "/" } else { "" };
+      Some(format!("{}{}{}", dir, file, slash))
+    }).collect();
+    candidates.sort();
+    (start, candidates)
+  }
+}
+
+/// Returns the longest prefix shared by every candidate.
+pub fn common_prefix(candidates: &[String]) -> &str {
+  let first = match candidates.first() {
+    Some(first) => first.as_str(),
+    None => return ""
+  };
+  let mut end = first.len();
+  for c in &candidates[1..] {
+    end = first[..end].char_indices().zip(c.chars()).find(|((_, a), b)| a != b).map_or(end.min(c.len()), |((i, _), _)| i);
+  }
+  &first[..end]
+}
+
+// the byte index after the whitespace before the word at `pos`
+fn word_start(line: &str, pos: usize) -> usize {
+  line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + line[i..].chars().next().map_or(1, char::len_utf8))
+}
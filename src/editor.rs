@@ -0,0 +1,475 @@
+//! Editing text with a cursor, a single line as done by `Terminal::ask` and `Terminal::mask`, or several as done by `Terminal::text_area`.
+
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::Zeroize;
+
+use crate::cli::{Key, Modifiers};
+use crate::width;
+
+/// A line of text with a cursor between its grapheme clusters, edited with emacs style keys like most shells.
+///
+/// | Keys | Edit |
+/// |------|------|
+/// | ArrowLeft, Ctrl-B / ArrowRight, Ctrl-F | Moves one cluster left / right |
+/// | Ctrl-ArrowLeft, Alt-B / Ctrl-ArrowRight, Alt-F | Moves one word left / right |
+/// | Home, Ctrl-A / End, Ctrl-E | Moves to the start / end |
+/// | Backspace / Delete, Ctrl-D | Deletes the cluster before / after the cursor |
+/// | Ctrl-W, Alt-Backspace / Alt-D | Deletes the word before / after the cursor |
+/// | Ctrl-U / Ctrl-K | Deletes everything before / after the cursor |
+///
+/// Words are separated by whitespace.
+///
+/// The text is overwritten with zeros when it is replaced or outgrows its allocation, and when the editor is dropped,
+/// so a password typed into it does not linger in memory.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::cli::Key;
+/// # use utile_cli::editor::LineEditor;
+/// let mut line = LineEditor::with_text("/usr/lbi/x");
+/// line.handle_key(&Key::Home);
+/// line.insert("ls ");
+/// line.handle_key(&Key::ArrowRight);
+/// assert_eq!(line.text(), "ls /usr/lbi/x");
+/// assert_eq!(line.cursor(), 4);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineEditor {
+  text: String,
+  // a byte index at a cluster boundary
+  cursor: usize
+}
+
+impl LineEditor {
+  /// Returns an empty line.
+  pub fn new() -> LineEditor {
+    LineEditor::default()
+  }
+
+  /// Returns a line of `text` with the cursor at its end.
+  pub fn with_text(text: &str) -> LineEditor {
+    LineEditor { text: text.to_string(), cursor: text.len() }
+  }
+
+  /// Returns the text of the line.
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// Replaces the text of the line, moving the cursor to its end.
+  pub fn set_text(&mut self, text: &str) {
+    self.text.zeroize();
+    self.reserve(text.len());
+    self.text.push_str(text);
+    self.cursor = text.len();
+  }
+
+  /// Returns the cursor as a byte index into the text.
+  pub fn cursor(&self) -> usize {
+    self.cursor
+  }
+
+  /// Returns the number of terminal cells before the cursor.
+  pub fn cursor_width(&self) -> usize {
+    width::width(&self.text[..self.cursor])
+  }
+
+  /// Returns the number of grapheme clusters before the cursor.
+  pub fn cursor_graphemes(&self) -> usize {
+    self.text[..self.cursor].graphemes(true).count()
+  }
+
+  /// Moves the cursor to the last cluster boundary at most `column` cells from the start of the line.
+  pub fn move_to_column(&mut self, column: usize) {
+    let mut x = 0;
+    for (i, g) in self.text.grapheme_indices(true) {
+      x += width::width(g);
+      if x > column {
+        self.cursor = i;
+        return;
+      }
+    }
+    self.cursor = self.text.len();
+  }
+
+  /// Inserts `s` at the cursor, leaving the cursor after it.
+  pub fn insert(&mut self, s: &str) {
+    self.reserve(s.len());
+    self.text.insert_str(self.cursor, s);
+    self.cursor += s.len();
+  }
+
+  /// Replaces the text from byte index `start` to the cursor with `completion`, leaving the cursor after it.
+  pub fn complete(&mut self, start: usize, completion: &str) {
+    self.reserve(completion.len());
+    self.text.replace_range(start..self.cursor, completion);
+    self.cursor = start + completion.len();
+  }
+
+  /// Moves the cursor one grapheme cluster left.
+  pub fn left(&mut self) {
+    self.cursor = self.prev_boundary(self.cursor);
+  }
+
+  /// Moves the cursor one grapheme cluster right.
+  pub fn right(&mut self) {
+    self.cursor = self.next_boundary(self.cursor);
+  }
+
+  /// Moves the cursor to the start of the word before it.
+  pub fn word_left(&mut self) {
+    self.cursor = self.word_start();
+  }
+
+  /// Moves the cursor to the end of the word after it.
+  pub fn word_right(&mut self) {
+    self.cursor = self.word_end();
+  }
+
+  /// Moves the cursor to the start of the line.
+  pub fn home(&mut self) {
+    self.cursor = 0;
+  }
+
+  /// Moves the cursor to the end of the line.
+  pub fn end(&mut self) {
+    self.cursor = self.text.len();
+  }
+
+  /// Deletes the grapheme cluster before the cursor.
+  pub fn delete_back(&mut self) {
+    let start = self.prev_boundary(self.cursor);
+    self.delete(start, self.cursor);
+  }
+
+  /// Deletes the grapheme cluster after the cursor.
+  pub fn delete_forward(&mut self) {
+    let end = self.next_boundary(self.cursor);
+    self.delete(self.cursor, end);
+  }
+
+  /// Deletes the word before the cursor, along with the whitespace between them.
+  pub fn delete_word_back(&mut self) {
+    let start = self.word_start();
+    self.delete(start, self.cursor);
+  }
+
+  /// Deletes the word after the cursor, along with the whitespace between them.
+  pub fn delete_word_forward(&mut self) {
+    let end = self.word_end();
+    self.delete(self.cursor, end);
+  }
+
+  /// Deletes everything before the cursor.
+  pub fn delete_to_start(&mut self) {
+    self.delete(0, self.cursor);
+  }
+
+  /// Deletes everything after the cursor.
+  pub fn delete_to_end(&mut self) {
+    self.delete(self.cursor, self.text.len());
+  }
+
+  /// Edits the line for `key`, returning false if the key is not an editing key.
+  ///
+  /// Keys typing a character insert it, see `Key::printable`.
+  pub fn handle_key(&mut self, key: &Key) -> bool {
+    if let Some(c) = key.printable() {
+      self.insert(c.encode_utf8(&mut [0; 4]));
+      return true;
+    }
+    match key {
+      Key::ArrowLeft => self.left(),
+      Key::ArrowRight => self.right(),
+      Key::Home => self.home(),
+      Key::End => self.end(),
+      Key::Backspace => self.delete_back(),
+      Key::Delete => self.delete_forward(),
+      Key::Modified { key: k, mods: Modifiers::CTRL } if **k == Key::ArrowLeft => self.word_left(),
+      Key::Modified { key: k, mods: Modifiers::CTRL } if **k == Key::ArrowRight => self.word_right(),
+      Key::Modified { key: k, mods: Modifiers::ALT } if **k == Key::Backspace => self.delete_word_back(),
+      k if *k == Key::ctrl('b') => self.left(),
+      k if *k == Key::ctrl('f') => self.right(),
+      k if *k == Key::ctrl('a') => self.home(),
+      k if *k == Key::ctrl('e') => self.end(),
+      k if *k == Key::ctrl('d') => self.delete_forward(),
+      k if *k == Key::ctrl('w') => self.delete_word_back(),
+      k if *k == Key::ctrl('u') => self.delete_to_start(),
+      k if *k == Key::ctrl('k') => self.delete_to_end(),
+      k if *k == Key::alt('b') => self.word_left(),
+      k if *k == Key::alt('f') => self.word_right(),
+      k if *k == Key::alt('d') => self.delete_word_forward(),
+      _ => return false
+    }
+    true
+  }
+
+  // makes room for `additional` bytes, moving the text itself so the old allocation is zeroed rather than freed as it is
+  fn reserve(&mut self, additional: usize) {
+    if self.text.len() + additional <= self.text.capacity() {
+      return;
+    }
+    let mut text = String::with_capacity((self.text.len() + additional).max(2 * self.text.capacity()));
+    text.push_str(&self.text);
+    self.text.zeroize();
+    self.text = text;
+  }
+
+  fn delete(&mut self, start: usize, end: usize) {
+    self.text.replace_range(start..end, "");
+    self.cursor = start;
+  }
+
+  fn prev_boundary(&self, i: usize) -> usize {
+    self.text[..i].grapheme_indices(true).next_back().map_or(0, |(j, _)| j)
+  }
+
+  fn next_boundary(&self, i: usize) -> usize {
+    i + self.text[i..].graphemes(true).next().map_or(0, str::len)
+  }
+
+  // the start of the word before the cursor, skipping whitespace first
+  fn word_start(&self) -> usize {
+    let mut i = self.cursor;
+    let mut in_word = false;
+    for (j, g) in self.text[..self.cursor].grapheme_indices(true).rev() {
+      let space = is_space(g);
+      if in_word && space {
+        break;
+      }
+      in_word |= !space;
+      i = j;
+    }
+    i
+  }
+
+  // the end of the word after the cursor, skipping whitespace first
+  fn word_end(&self) -> usize {
+    let mut i = self.cursor;
+    let mut in_word = false;
+    for g in self.text[self.cursor..].graphemes(true) {
+      let space = is_space(g);
+      if in_word && space {
+        break;
+      }
+      in_word |= !space;
+      i += g.len();
+    }
+    i
+  }
+}
+
+impl Drop for LineEditor {
+  fn drop(&mut self) {
+    self.text.zeroize();
+  }
+}
+
+/// Lines of text with a cursor, each edited like a `LineEditor`, where Enter starts a new line.
+///
+/// | Keys | Edit |
+/// |------|------|
+/// | ArrowUp, Ctrl-P / ArrowDown, Ctrl-N | Moves one line up / down, staying in the same column where possible |
+/// | Ctrl-Home / Ctrl-End | Moves to the start / end of the text |
+/// | Enter | Splits the line at the cursor |
+///
+/// Moving left at the start of a line or right at its end continues on the line before or after,
+/// and deleting across the start or end of a line joins it with its neighbour.
+/// Every other key edits the line of the cursor, see `LineEditor`.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::cli::Key;
+/// # use utile_cli::editor::TextEditor;
+/// let mut text = TextEditor::with_text("Fix bug\nin parser");
+/// text.handle_key(&Key::ArrowUp);
+/// text.handle_key(&Key::Enter);
+/// assert_eq!(text.text(), "Fix bug\n\nin parser");
+/// text.handle_key(&Key::Backspace);
+/// text.handle_key(&Key::Backspace);
+/// assert_eq!(text.text(), "Fix bu\nin parser");
+/// assert_eq!(text.row(), 0);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEditor {
+  // never empty
+  lines: Vec<LineEditor>,
+  row: usize
+}
+
+impl TextEditor {
+  /// Returns an empty text.
+  pub fn new() -> TextEditor {
+    TextEditor { lines: vec![LineEditor::new()], row: 0 }
+  }
+
+  /// Returns a text of `text`, split into lines at every '\n', with the cursor at its end.
+  pub fn with_text(text: &str) -> TextEditor {
+    let lines: Vec<LineEditor> = text.split('\n').map(LineEditor::with_text).collect();
+    TextEditor { row: lines.len() - 1, lines }
+  }
+
+  /// Returns the text, with the lines joined by '\n'.
+  pub fn text(&self) -> String {
+    self.lines().collect::<Vec<_>>().join("\n")
+  }
+
+  /// Returns the lines of the text.
+  pub fn lines(&self) -> impl Iterator<Item = &str> {
+    self.lines.iter().map(LineEditor::text)
+  }
+
+  /// Returns the number of lines, which is at least one.
+  pub fn line_count(&self) -> usize {
+    self.lines.len()
+  }
+
+  /// Returns the index of the line with the cursor.
+  pub fn row(&self) -> usize {
+    self.row
+  }
+
+  /// Returns the line with the cursor.
+  pub fn line(&self) -> &LineEditor {
+    &self.lines[self.row]
+  }
+
+  /// Inserts `s` at the cursor, starting a new line at every '\n', leaving the cursor after it.
+  pub fn insert(&mut self, s: &str) {
+    for (i, part) in s.split('\n').enumerate() {
+      if i > 0 {
+        self.newline();
+      }
+      self.lines[self.row].insert(part);
+    }
+  }
+
+  /// Splits the line at the cursor, moving the cursor to the start of the new line.
+  pub fn newline(&mut self) {
+    let line = &mut self.lines[self.row];
+    let rest = line.text()[line.cursor()..].to_string();
+    line.delete_to_end();
+    let mut next = LineEditor::with_text(&rest);
+    next.home();
+    self.row += 1;
+    self.lines.insert(self.row, next);
+  }
+
+  /// Moves the cursor one line up.
+  pub fn up(&mut self) {
+    if self.row > 0 {
+      self.move_to_row(self.row - 1);
+    }
+  }
+
+  /// Moves the cursor one line down.
+  pub fn down(&mut self) {
+    if self.row + 1 < self.lines.len() {
+      self.move_to_row(self.row + 1);
+    }
+  }
+
+  /// Moves the cursor one grapheme cluster left, to the end of the line before at the start of a line.
+  pub fn left(&mut self) {
+    if self.line().cursor() == 0 && self.row > 0 {
+      self.row -= 1;
+      self.lines[self.row].end();
+    } else {
+      self.lines[self.row].left();
+    }
+  }
+
+  /// Moves the cursor one grapheme cluster right, to the start of the line after at the end of a line.
+  pub fn right(&mut self) {
+    if self.at_line_end() && self.row + 1 < self.lines.len() {
+      self.row += 1;
+      self.lines[self.row].home();
+    } else {
+      self.lines[self.row].right();
+    }
+  }
+
+  /// Moves the cursor to the start of the text.
+  pub fn start(&mut self) {
+    self.row = 0;
+    self.lines[0].home();
+  }
+
+  /// Moves the cursor to the end of the text.
+  pub fn end(&mut self) {
+    self.row = self.lines.len() - 1;
+    self.lines[self.row].end();
+  }
+
+  /// Deletes the grapheme cluster before the cursor, joining the line with the one before at the start of a line.
+  pub fn delete_back(&mut self) {
+    if self.line().cursor() == 0 && self.row > 0 {
+      self.row -= 1;
+      self.lines[self.row].end();
+      self.join_next();
+    } else {
+      self.lines[self.row].delete_back();
+    }
+  }
+
+  /// Deletes the grapheme cluster after the cursor, joining the line with the one after at the end of a line.
+  pub fn delete_forward(&mut self) {
+    if self.at_line_end() && self.row + 1 < self.lines.len() {
+      self.join_next();
+    } else {
+      self.lines[self.row].delete_forward();
+    }
+  }
+
+  /// Edits the text for `key`, returning false if the key is not an editing key.
+  pub fn handle_key(&mut self, key: &Key) -> bool {
+    match key {
+      Key::Enter => self.newline(),
+      Key::ArrowUp => self.up(),
+      Key::ArrowDown => self.down(),
+      Key::ArrowLeft => self.left(),
+      Key::ArrowRight => self.right(),
+      Key::Backspace => self.delete_back(),
+      Key::Delete => self.delete_forward(),
+      Key::Modified { key: k, mods: Modifiers::CTRL } if **k == Key::Home => self.start(),
+      Key::Modified { key: k, mods: Modifiers::CTRL } if **k == Key::End => self.end(),
+      k if *k == Key::ctrl('p') => self.up(),
+      k if *k == Key::ctrl('n') => self.down(),
+      k if *k == Key::ctrl('b') => self.left(),
+      k if *k == Key::ctrl('f') => self.right(),
+      k if *k == Key::ctrl('d') => self.delete_forward(),
+      k => return self.lines[self.row].handle_key(k)
+    }
+    true
+  }
+
+  fn at_line_end(&self) -> bool {
+    self.line().cursor() == self.line().text().len()
+  }
+
+  fn move_to_row(&mut self, row: usize) {
+    let column = self.line().cursor_width();
+    self.row = row;
+    self.lines[row].move_to_column(column);
+  }
+
+  // appends the line after the cursor's to it, keeping the cursor where it is
+  fn join_next(&mut self) {
+    let next = self.lines.remove(self.row + 1);
+    let line = &mut self.lines[self.row];
+    let column = line.cursor_width();
+    line.end();
+    line.insert(next.text());
+    line.move_to_column(column);
+  }
+}
+
+impl Default for TextEditor {
+  fn default() -> TextEditor {
+    TextEditor::new()
+  }
+}
+
+fn is_space(g: &str) -> bool {
+  g.chars().all(char::is_whitespace)
+}
@@ -0,0 +1,60 @@
+use std::fmt;
+
+/// Errors returned by the fallible (`try_*`) functions of this crate.
+/// 
+/// The non `try_*` versions of the layer functions, such as `Layer2D::index` and `Terminal::layer_front`, panic with the same message instead.
+/// Those of the prompts fall back to a default when input ends, the prompt is cancelled or it times out:
+/// `yesno` answers with its default, `choices`, `ask`, `ask_with`, `mask` and `text_area` with an empty string,
+/// `ask_parse` with `T::default()`, `ask_number` with zero (or the bound nearest to it) and `password` with an empty secret.
+/// `yesno` and `choices` still panic when given an invalid suffix or no choices, and `run` simply returns once input ends.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  /// The input source ran out of keys before a prompt was answered.
+  InputEnded,
+  /// A prompt was left with a key bound to `Action::Cancel`.
+  Cancelled,
+  /// No key was pressed within the prompt timeout, see `TerminalBuilder::prompt_timeout`.
+  TimedOut,
+  /// A key name in an input script could not be recognized.
+  InvalidKey(String),
+  /// A y/n suffix did not contain a '/' separating the yes and the no.
+  InvalidSuffix(String),
+  /// A choice prompt was given no choices.
+  NoChoices,
+  /// The layer stack is empty.
+  EmptyLayerStack,
+  /// No layer is at this location in the layer stack, see `Terminal::layer_locate`.
+  LayerNotFound(i32),
+  /// A position is outside of a layer2D, in the form (x, y).
+  OutOfBounds(usize, usize),
+  /// An action name in a keymap could not be recognized.
+  InvalidAction(String),
+  /// A line of a keymap config is not a binding, in the form (line number, line).
+  InvalidBinding(usize, String),
+  /// A file could not be read or written.
+  Io(String)
+}
+
+/// A result with this crate's `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::InputEnded => write!(f, "input ended before the prompt was answered"),
+      Error::Cancelled => write!(f, "the prompt was cancelled"),
+      Error::TimedOut => write!(f, "timed out waiting for a key"),
+      Error::InvalidKey(name) => write!(f, "unknown key name '{}'", name),
+      Error::InvalidSuffix(suffix) => write!(f, "expected a '/' character separating a yes no question, got '{}'", suffix),
+      Error::NoChoices => write!(f, "no choices were given"),
+      Error::EmptyLayerStack => write!(f, "the layer stack is empty"),
+      Error::LayerNotFound(l) => write!(f, "no layer at location {} in the layer stack", l),
+      Error::OutOfBounds(x, y) => write!(f, "position ({}, {}) is outside of the layer2D", x, y),
+      Error::InvalidAction(name) => write!(f, "unknown action name '{}'", name),
+      Error::InvalidBinding(n, line) => write!(f, "expected a binding of the form 'key = action' on line {}, got '{}'", n, line),
+      Error::Io(e) => write!(f, "i/o error: {}", e)
+    }
+  }
+}
+
+impl std::error::Error for Error {}
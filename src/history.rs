@@ -0,0 +1,148 @@
+//! Remembering the answers of `Terminal::ask`, optionally in a file.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// The previous answers of `ask`, oldest first, set with `Terminal::set_history`.
+///
+/// Prompts walk through the history with ArrowUp and ArrowDown (or Ctrl-P and Ctrl-N),
+/// and Ctrl-R searches backwards for an answer containing the typed text.
+/// Empty answers are not remembered, and once the history is full the oldest answers are dropped.
+/// Duplicates are removed by default, keeping the newest one.
+///
+/// A history created with `load` saves itself to its file every time an answer is added.
+///
+/// # Examples
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # use utile_cli::history::History;
+/// let mut t = Terminal::new();
+/// t.set_history(Some(History::load(".repl_history", 500).unwrap()));
+/// loop {
+///   let command = t.ask("> ".into());
+///   t.outbr();
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct History {
+  entries: VecDeque<String>,
+  max_len: usize,
+  ignore_dups: bool,
+  path: Option<PathBuf>
+}
+
+impl History {
+  /// Returns an empty history of at most `max_len` entries, kept in memory only.
+  pub fn new(max_len: usize) -> History {
+    History { entries: VecDeque::new(), max_len, ignore_dups: true, path: None }
+  }
+
+  /// Reads a history of at most `max_len` entries from `path`, one entry per line, which it is then saved to.
+  ///
+  /// A missing file is treated as an empty history and created on the first save.
+  pub fn load<P: AsRef<Path>>(path: P, max_len: usize) -> Result<History> {
+    let mut history = History::new(max_len);
+    match std::fs::read_to_string(path.as_ref()) {
+      Ok(contents) => contents.lines().for_each(|l| history.add(l)),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+      Err(e) => return Err(Error::Io(e.to_string()))
+    }
+    history.path = Some(path.as_ref().to_path_buf());
+    Ok(history)
+  }
+
+  /// Sets whether adding an entry removes earlier copies of it, true by default.
+  pub fn set_ignore_dups(&mut self, ignore: bool) -> &mut History {
+    self.ignore_dups = ignore;
+    self
+  }
+
+  /// Adds an entry as the newest, saving the history if it was loaded from a file.
+  ///
+  /// Empty entries are ignored. Entries are kept to a single line, as they are saved one per line.
+  pub fn push(&mut self, entry: &str) -> Result<()> {
+    self.add(entry);
+    self.save()
+  }
+
+  /// Writes the history to the file it was loaded from, doing nothing for a history kept in memory.
+  pub fn save(&self) -> Result<()> {
+    match &self.path {
+      Some(path) => {
+        let contents: String = self.entries.iter().map(|e| format!("{}\n", e)).collect();
+        std::fs::write(path, contents).map_err(|e| Error::Io(e.to_string()))
+      },
+      None => Ok(())
+    }
+  }
+
+  /// Returns the file the history is saved to.
+  pub fn path(&self) -> Option<&Path> {
+    self.path.as_deref()
+  }
+
+  /// Returns entry `i`, counting from the oldest.
+  pub fn get(&self, i: usize) -> Option<&str> {
+    self.entries.get(i).map(String::as_str)
+  }
+
+  /// Returns the entries, oldest first.
+  pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> {
+    self.entries.iter().map(String::as_str)
+  }
+
+  /// Returns the number of entries.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns true if there are no entries.
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Removes every entry, without saving.
+  pub fn clear(&mut self) {
+    self.entries.clear();
+  }
+
+  /// Returns the index of the newest entry before `before` containing `query`.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::history::History;
+  /// let mut h = History::new(10);
+  /// for entry in ["git status", "ls", "git log"].iter() {
+  ///   h.push(entry).unwrap();
+  /// }
+  /// assert_eq!(h.search("git", h.len()), Some(2));
+  /// assert_eq!(h.search("git", 2), Some(0));
+  /// assert_eq!(h.search("cd", h.len()), None);
+  /// ```
+  pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+    self.entries.iter().take(before).rposition(|e| e.contains(query))
+  }
+
+  fn add(&mut self, entry: &str) {
+    let entry = entry.replace(['\r', '\n'], " ");
+    if entry.is_empty() || self.max_len == 0 {
+      return;
+    }
+    if self.ignore_dups {
+      self.entries.retain(|e| *e != entry);
+    }
+    self.entries.push_back(entry);
+    while self.entries.len() > self.max_len {
+      self.entries.pop_front();
+    }
+  }
+}
+
+impl Default for History {
+  /// Returns an empty history of at most 1000 entries, kept in memory only.
+  fn default() -> History {
+    History::new(1000)
+  }
+}
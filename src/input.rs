@@ -0,0 +1,193 @@
+//! Sources of keys and other events other than the backend, such as prepared scripts.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::cli::{Event, Key, Modifiers};
+use crate::error::{Error, Result};
+
+/// A source of events that a `Terminal` reads from instead of its backend.
+///
+/// # Examples
+/// Driving a prompt from a script:
+/// ```
+/// # use utile_cli::backend::VirtualBackend;
+/// # use utile_cli::cli::*;
+/// # use utile_cli::input::ScriptedInput;
+/// let mut t = Terminal::with_backend(VirtualBackend::new(20, 5));
+/// t.set_input(ScriptedInput::parse("abd<Backspace>c<Enter>").unwrap());
+/// assert_eq!(t.try_ask("> ".into()).unwrap(), "abc");
+/// ```
+pub trait InputSource {
+  /// Returns the next event, or `None` once the source has run out of events.
+  fn read_event(&mut self) -> Option<Event>;
+
+  /// Waits up to `timeout` for the next event, returning `Ok(None)` if none arrived in time,
+  /// or `Error::InputEnded` once the source has run out of events.
+  /// 
+  /// The default waits without a timeout, which suits sources that never wait, such as `ScriptedInput`.
+  fn read_event_timeout(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+    self.read_event().map(Some).ok_or(Error::InputEnded)
+  }
+}
+
+/// An input source that returns a prepared sequence of events, then ends.
+///
+/// Prompts reading past the end of the sequence return `Error::InputEnded` from their `try_*` versions instead of waiting for more keys.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedInput {
+  events: VecDeque<Event>
+}
+
+impl ScriptedInput {
+  /// Returns an input source over `keys`.
+  pub fn new<I: IntoIterator<Item = Key>>(keys: I) -> ScriptedInput {
+    ScriptedInput { events: keys.into_iter().map(Event::Key).collect() }
+  }
+
+  /// Parses a text script into keys.
+  ///
+  /// Every character is a key of its own, with `\n` being `Enter` and a space being `Space` like the backends' default key tables.
+  /// Named keys are written between angle brackets, for example `<Enter>`, `<Backspace>`, `<Up>` or `<F3>`, and `<lt>` is a literal `<`.
+  /// Modifiers are written before the key name, for example `<C-a>` or `<C-S-Left>`.
+  /// Key names are not case sensitive, see `Key::from_str`.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::cli::{Event, Key};
+  /// # use utile_cli::input::ScriptedInput;
+  /// let events = ScriptedInput::parse("a<Enter>").unwrap().remaining();
+  /// assert_eq!(events, vec![Event::Key(Key::char('a')), Event::Key(Key::Enter)]);
+  /// ```
+  pub fn parse(script: &str) -> Result<ScriptedInput> {
+    let mut keys = VecDeque::new();
+    let mut chars = script.chars();
+    while let Some(c) = chars.next() {
+      match c {
+        '<' => {
+          let mut name = String::new();
+          loop {
+            match chars.next() {
+              Some('>') => break,
+              Some(c) => name.push(c),
+              None => return Err(Error::InvalidKey(format!("<{}", name)))
+            }
+          }
+          keys.push_back(name.parse()?);
+        },
+        c => keys.push_back(c.to_string().parse()?),
+      }
+    }
+    Ok(ScriptedInput::new(keys))
+  }
+
+  /// Appends keys to the end of the script.
+  pub fn push<I: IntoIterator<Item = Key>>(&mut self, keys: I) {
+    self.events.extend(keys.into_iter().map(Event::Key));
+  }
+
+  /// Appends an event, such as a resize, to the end of the script.
+  pub fn push_event(&mut self, event: Event) {
+    self.events.push_back(event);
+  }
+
+  /// Returns the number of events that have not been read yet.
+  pub fn len(&self) -> usize {
+    self.events.len()
+  }
+
+  /// Returns true if every event has been read.
+  pub fn is_empty(&self) -> bool {
+    self.events.is_empty()
+  }
+
+  /// Returns a copy of the events that have not been read yet.
+  pub fn remaining(&self) -> Vec<Event> {
+    self.events.iter().cloned().collect()
+  }
+}
+
+impl InputSource for ScriptedInput {
+  fn read_event(&mut self) -> Option<Event> {
+    self.events.pop_front()
+  }
+}
+
+impl FromStr for Key {
+  type Err = Error;
+
+  /// Parses a key name such as `Enter`, `Up`, `ArrowUp`, `PageDown`, `F3`, `lt` or a single character.
+  ///
+  /// Names may be prefixed with modifiers, `C-` for Ctrl, `A-` or `M-` for Alt and `S-` for Shift, such as `C-a` or `S-Tab`.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::cli::{Key, Modifiers};
+  /// assert_eq!("C-a".parse(), Ok(Key::ctrl('a')));
+  /// assert_eq!("c-s-left".parse(), Ok(Key::ArrowLeft.with_mods(Modifiers::CTRL | Modifiers::SHIFT)));
+  /// ```
+  fn from_str(s: &str) -> Result<Key> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+      (Some('\n'), None) => return Ok(Key::Enter),
+      (Some(' '), None) => return Ok(Key::Space),
+      (Some(c), None) => return Ok(Key::char(c)),
+      _ => ()
+    }
+    let mut mods = Modifiers::NONE;
+    let mut name = s;
+    while name.len() > 2 && name.as_bytes()[1] == b'-' {
+      mods = mods | match name.as_bytes()[0].to_ascii_lowercase() {
+        b'c' => Modifiers::CTRL,
+        b'a' | b'm' => Modifiers::ALT,
+        b's' => Modifiers::SHIFT,
+        _ => break
+      };
+      name = &name[2..];
+    }
+    if !mods.is_empty() {
+      let key = name.parse::<Key>().map_err(|_| Error::InvalidKey(s.to_string()))?;
+      // Ctrl characters are lowercase
+      let key = match key {
+        Key::Char { c, mods: m } if mods.ctrl => Key::Char { c: c.to_ascii_lowercase(), mods: m },
+        key => key
+      };
+      return Ok(key.with_mods(mods));
+    }
+    let key = match s.to_lowercase().as_str() {
+      "enter" | "return" | "cr" => Key::Enter,
+      "tab" => Key::Tab,
+      "backtab" => Key::BackTab,
+      "escape" | "esc" => Key::Escape,
+      "space" => Key::Space,
+      "backspace" | "bs" => Key::Backspace,
+      "delete" | "del" => Key::Delete,
+      "insert" | "ins" => Key::Insert,
+      "home" => Key::Home,
+      "end" => Key::End,
+      "pageup" | "pgup" => Key::PageUp,
+      "pagedown" | "pgdn" => Key::PageDown,
+      "up" | "arrowup" => Key::ArrowUp,
+      "down" | "arrowdown" => Key::ArrowDown,
+      "left" | "arrowleft" => Key::ArrowLeft,
+      "right" | "arrowright" => Key::ArrowRight,
+      "lt" => Key::char('<'),
+      "gt" => Key::char('>'),
+      "f1" => Key::F1,
+      "f2" => Key::F2,
+      "f3" => Key::F3,
+      "f4" => Key::F4,
+      "f5" => Key::F5,
+      "f6" => Key::F6,
+      "f7" => Key::F7,
+      "f8" => Key::F8,
+      "f9" => Key::F9,
+      "f10" => Key::F10,
+      "f11" => Key::F11,
+      "f12" => Key::F12,
+      _ => return Err(Error::InvalidKey(s.to_string()))
+    };
+    Ok(key)
+  }
+}
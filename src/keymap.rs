@@ -0,0 +1,170 @@
+//! Binding keys to the actions of prompts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::cli::Key;
+use crate::error::{Error, Result};
+
+/// Something a key can do in a prompt.
+///
+/// Prompts only use the actions they have a meaning for, and treat keys bound to other actions like unbound keys.
+/// For example `ask` only uses `Submit` and `Cancel`, so a character bound to `Next` is still typed into it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+  /// Answers the prompt.
+  Submit,
+  /// Leaves the prompt without answering, see `Error::Cancelled`.
+  Cancel,
+  /// Moves to the next choice, or to no in `yesno`.
+  Next,
+  /// Moves to the previous choice, or to yes in `yesno`.
+  Prev,
+  /// Switches between yes and no in `yesno`.
+  Toggle
+}
+
+/// A mapping from keys to actions, consulted by the prompts of a `Terminal`.
+///
+/// # Examples
+/// Adding vim keys to the defaults:
+/// ```
+/// # use utile_cli::cli::Key;
+/// # use utile_cli::keymap::{Action, Keymap};
+/// let keymap = Keymap::parse("
+///   j = Next
+///   k = Prev
+///   <C-c> = Cancel
+/// ").unwrap();
+/// assert_eq!(keymap.action(&Key::char('j')), Some(Action::Next));
+/// assert_eq!(keymap.action(&Key::ArrowDown), Some(Action::Next));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+  bindings: HashMap<Key, Action>
+}
+
+impl Keymap {
+  /// Returns a keymap without any bindings, see `Keymap::default` for the built-in ones.
+  pub fn new() -> Keymap {
+    Keymap { bindings: HashMap::new() }
+  }
+
+  /// Binds `key` to `action`, returning the action it was bound to before.
+  pub fn bind(&mut self, key: Key, action: Action) -> Option<Action> {
+    self.bindings.insert(key, action)
+  }
+
+  /// Removes the binding of `key`.
+  pub fn unbind(&mut self, key: &Key) -> Option<Action> {
+    self.bindings.remove(key)
+  }
+
+  /// Returns the action `key` is bound to.
+  pub fn action(&self, key: &Key) -> Option<Action> {
+    self.bindings.get(key).copied()
+  }
+
+  /// Returns the keys bound to `action`.
+  pub fn keys(&self, action: Action) -> Vec<Key> {
+    self.bindings.iter().filter(|(_, &a)| a == action).map(|(k, _)| k.clone()).collect()
+  }
+
+  /// Parses bindings over the defaults.
+  ///
+  /// Every line binds a key to an action in the form `key = action`, and lines starting with `#` being comments.
+  /// Keys are written like in scripts, with or without angle brackets, see `Key::from_str`.
+  /// The action `none` removes the default binding of a key.
+  ///
+  /// Returns `Error::InvalidBinding` with the line number for a line that is not a binding.
+  pub fn parse(config: &str) -> Result<Keymap> {
+    let mut keymap = Keymap::default();
+    keymap.extend_from(config)?;
+    Ok(keymap)
+  }
+
+  /// Reads bindings over the defaults from a config file, see `parse`.
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Keymap> {
+    let config = std::fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+    Keymap::parse(&config)
+  }
+
+  /// Parses bindings over the ones already in the keymap, see `parse`.
+  pub fn extend_from(&mut self, config: &str) -> Result<()> {
+    for (i, line) in config.lines().enumerate() {
+      let binding = line.trim();
+      if binding.is_empty() || binding.starts_with('#') {
+        continue;
+      }
+      let invalid = || Error::InvalidBinding(i + 1, line.trim().to_string());
+      let (key, action) = binding.rsplit_once('=').ok_or_else(invalid)?;
+      let key = key.trim();
+      let key = match key.strip_prefix('<').and_then(|k| k.strip_suffix('>')) {
+        Some(name) if !name.is_empty() => name,
+        _ => key
+      };
+      let key: Key = key.parse().map_err(|_| invalid())?;
+      match action.trim() {
+        a if a.eq_ignore_ascii_case("none") => { self.unbind(&key); },
+        a => { self.bind(key, a.parse().map_err(|_| invalid())?); }
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Default for Keymap {
+  /// Returns the built-in bindings: Enter submits, Escape and Ctrl-C cancel, Down, Right and Tab move to the next choice,
+  /// Up, Left and BackTab move to the previous choice, and Space toggles.
+  fn default() -> Keymap {
+    let mut keymap = Keymap::new();
+    let bindings = [
+      (Key::Enter, Action::Submit),
+      (Key::Escape, Action::Cancel),
+      (Key::ctrl('c'), Action::Cancel),
+      (Key::ArrowDown, Action::Next),
+      (Key::ArrowRight, Action::Next),
+      (Key::Tab, Action::Next),
+      (Key::ArrowUp, Action::Prev),
+      (Key::ArrowLeft, Action::Prev),
+      (Key::BackTab, Action::Prev),
+      (Key::Space, Action::Toggle)
+    ];
+    for (key, action) in bindings.iter() {
+      keymap.bind(key.clone(), *action);
+    }
+    keymap
+  }
+}
+
+impl FromStr for Action {
+  type Err = Error;
+
+  /// Parses an action name, which is not case sensitive.
+  fn from_str(s: &str) -> Result<Action> {
+    let action = match s.to_lowercase().as_str() {
+      "submit" => Action::Submit,
+      "cancel" => Action::Cancel,
+      "next" => Action::Next,
+      "prev" | "previous" => Action::Prev,
+      "toggle" => Action::Toggle,
+      _ => return Err(Error::InvalidAction(s.to_string()))
+    };
+    Ok(action)
+  }
+}
+
+impl fmt::Display for Action {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let name = match self {
+      Action::Submit => "Submit",
+      Action::Cancel => "Cancel",
+      Action::Next => "Next",
+      Action::Prev => "Prev",
+      Action::Toggle => "Toggle"
+    };
+    write!(f, "{}", name)
+  }
+}
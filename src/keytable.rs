@@ -0,0 +1,301 @@
+//! Mapping the raw input of backends to keys.
+
+use std::collections::HashMap;
+
+use crate::cli::{Key, Modifiers};
+
+/// A table mapping raw input to keys, used by backends to normalize the input of different terminals.
+///
+/// Byte sequences, such as control bytes and escape sequences, are decoded by `AnsiBackend`, and curses input by `CursesBackend`.
+/// `KeyTable::default` has mappings for common terminals, which can be extended or overridden and then applied with `TerminalBuilder::key_table`.
+///
+/// Escape sequences carrying modifiers, such as `\x1b[1;5D` for Ctrl + ArrowLeft, are looked up without them (`\x1b[D`) if they are not in the table themselves.
+/// Likewise the extended keys curses names after them, such as `kLFT5`, are looked up as the curses input of the key without modifiers.
+/// Input that is not in the table is decoded as a character, with control characters becoming Ctrl and a letter.
+///
+/// # Examples
+/// ```no_run
+/// # use utile_cli::backend::AnsiBackend;
+/// # use utile_cli::builder::TerminalBuilder;
+/// # use utile_cli::cli::Key;
+/// # use utile_cli::keytable::KeyTable;
+/// let mut table = KeyTable::default();
+/// table.insert(b"\x1b[8~", Key::End);          // rxvt
+/// table.insert(b"\x08", Key::ctrl('h'));       // Ctrl-H instead of Backspace
+/// let t = TerminalBuilder::new().key_table(table).build_with(AnsiBackend::stdio());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyTable {
+  bytes: HashMap<Vec<u8>, Key>,
+  #[cfg(feature = "curses")]
+  curses: HashMap<pancurses::Input, Key>
+}
+
+impl KeyTable {
+  /// Returns a table without any mappings, see `KeyTable::default` for the built-in ones.
+  pub fn new() -> KeyTable {
+    KeyTable {
+      bytes: HashMap::new(),
+      #[cfg(feature = "curses")]
+      curses: HashMap::new()
+    }
+  }
+
+  /// Maps a byte sequence to `key`, returning the key it was mapped to before.
+  pub fn insert(&mut self, bytes: &[u8], key: Key) -> Option<Key> {
+    self.bytes.insert(bytes.to_vec(), key)
+  }
+
+  /// Removes the mapping of a byte sequence, leaving it to be decoded as characters.
+  pub fn remove(&mut self, bytes: &[u8]) -> Option<Key> {
+    self.bytes.remove(bytes)
+  }
+
+  /// Returns the key a byte sequence is mapped to.
+  pub fn get(&self, bytes: &[u8]) -> Option<&Key> {
+    self.bytes.get(bytes)
+  }
+
+  /// Maps curses input to `key`, returning the key it was mapped to before.
+  #[cfg(feature = "curses")]
+  pub fn insert_curses(&mut self, input: pancurses::Input, key: Key) -> Option<Key> {
+    self.curses.insert(input, key)
+  }
+
+  /// Removes the mapping of curses input.
+  #[cfg(feature = "curses")]
+  pub fn remove_curses(&mut self, input: pancurses::Input) -> Option<Key> {
+    self.curses.remove(&input)
+  }
+
+  /// Returns the key curses input is mapped to.
+  #[cfg(feature = "curses")]
+  pub fn get_curses(&self, input: pancurses::Input) -> Option<&Key> {
+    self.curses.get(&input)
+  }
+
+  /// Decodes a byte sequence as the backends do, returning `None` if it is not a single key.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::cli::{Key, Modifiers};
+  /// # use utile_cli::keytable::KeyTable;
+  /// let table = KeyTable::default();
+  /// assert_eq!(table.decode(b"\x7f"), Some(Key::Backspace));
+  /// assert_eq!(table.decode(b"\x1b[1;5D"), Some(Key::ArrowLeft.with_mods(Modifiers::CTRL)));
+  /// assert_eq!(table.decode(b"\x01"), Some(Key::ctrl('a')));
+  /// ```
+  pub fn decode(&self, bytes: &[u8]) -> Option<Key> {
+    if let Some(key) = self.bytes.get(bytes) {
+      return Some(key.clone());
+    }
+    match bytes {
+      [0x1b, b'[', rest @ ..] => self.decode_csi(rest),
+      [0x1b, b'O', ..] => None,
+      [0x1b, rest @ ..] if !rest.is_empty() => self.decode(rest).map(|k| k.with_mods(Modifiers::ALT)),
+      [b] if *b < 0x80 => Some(control(*b as char).unwrap_or_else(|| Key::char(*b as char))),
+      _ => {
+        let mut chars = std::str::from_utf8(bytes).ok()?.chars();
+        match (chars.next(), chars.next()) {
+          (Some(c), None) => Some(Key::char(c)),
+          _ => None
+        }
+      }
+    }
+  }
+
+  // looks up a control sequence without its modifier parameter, then adds the modifiers
+  fn decode_csi(&self, rest: &[u8]) -> Option<Key> {
+    let (last, params) = rest.split_last()?;
+    let params = std::str::from_utf8(params).ok()?;
+    let mut params = params.split(';');
+    let code = params.next().unwrap_or("");
+    // the modifier parameter is one more than a bitmask of shift (1), alt (2) and ctrl (4), with meta (8) treated as alt
+    let bits = params.next()?.parse::<u8>().ok()?.saturating_sub(1);
+    let mods = Modifiers { shift: bits & 1 != 0, alt: bits & (2 | 8) != 0, ctrl: bits & 4 != 0 };
+    let base = match code {
+      "" | "1" => format!("\x1b[{}", *last as char),
+      code => format!("\x1b[{}{}", code, *last as char)
+    };
+    self.bytes.get(base.as_bytes()).map(|k| k.clone().with_mods(mods))
+  }
+
+  /// Decodes curses input as `CursesBackend` does, returning `None` if it is not a key.
+  #[cfg(feature = "curses")]
+  pub fn decode_curses(&self, input: pancurses::Input) -> Option<Key> {
+    if let Some(key) = self.curses.get(&input) {
+      return Some(key.clone());
+    }
+    match input {
+      pancurses::Input::Character(c) => Some(control(c).unwrap_or(Key::char(c))),
+      // extended keys get their codes when curses loads the terminal's description, so they are known by name
+      #[cfg(unix)]
+      pancurses::Input::Unknown(code) => self.decode_curses_name(&ncurses::keyname(code)?),
+      _ => None
+    }
+  }
+
+  /// Decodes several curses inputs read together as `CursesBackend` does, returning `None` if they are not a key.
+  ///
+  /// Like `decode`, Escape followed by a key is that key with Alt, as terminals send it.
+  #[cfg(feature = "curses")]
+  pub fn decode_curses_seq(&self, inputs: &[pancurses::Input]) -> Option<Key> {
+    match inputs {
+      [input] => self.decode_curses(*input),
+      [pancurses::Input::Character('\x1b'), rest @ ..] if !rest.is_empty() => self.decode_curses_seq(rest).map(|k| k.with_mods(Modifiers::ALT)),
+      _ => None
+    }
+  }
+
+  /// Decodes the name curses gives an extended key, such as `kLFT5` for Ctrl + ArrowLeft, returning `None` if it is not a key.
+  ///
+  /// The name ends in the same modifier parameter as escape sequences, from 2 for Shift up to 8 for Ctrl + Alt + Shift.
+  #[cfg(feature = "curses")]
+  pub fn decode_curses_name(&self, name: &str) -> Option<Key> {
+    use pancurses::Input;
+    let param = name.chars().last()?.to_digit(10).filter(|p| (2..=8).contains(p))?;
+    let base = &name[..name.len() - 1];
+    let bits = param - 1;
+    let mods = Modifiers { shift: bits & 1 != 0, alt: bits & 2 != 0, ctrl: bits & 4 != 0 };
+    let input = match base {
+      "kUP" => Input::KeyUp,
+      "kDN" => Input::KeyDown,
+      "kLFT" => Input::KeyLeft,
+      "kRIT" => Input::KeyRight,
+      "kHOM" => Input::KeyHome,
+      "kEND" => Input::KeyEnd,
+      "kPRV" => Input::KeyPPage,
+      "kNXT" => Input::KeyNPage,
+      "kDC" => Input::KeyDC,
+      "kIC" => Input::KeyIC,
+      _ => return None
+    };
+    self.curses.get(&input).map(|k| k.clone().with_mods(mods))
+  }
+}
+
+// the Ctrl key which types a control character, such as Ctrl + a for '\x01'
+fn control(c: char) -> Option<Key> {
+  match c as u32 {
+    0x00 => Some(Key::ctrl(' ')),
+    b @ 0x01..=0x1a => Some(Key::ctrl((b as u8 + 0x60) as char)),
+    b @ 0x1b..=0x1f => Some(Key::ctrl((b as u8 + 0x40) as char)),
+    _ => None
+  }
+}
+
+impl Default for KeyTable {
+  /// Returns a table with mappings for xterm, the linux console, rxvt, and curses.
+  fn default() -> KeyTable {
+    let mut t = KeyTable::new();
+    let bytes: &[(&[u8], Key)] = &[
+      (b"\r", Key::Enter),
+      (b"\n", Key::Enter),
+      (b"\t", Key::Tab),
+      (b" ", Key::Space),
+      (b"\x7f", Key::Backspace),
+      (b"\x08", Key::Backspace),
+      (b"\x1b", Key::Escape),
+      (b"\x1b\x1b", Key::Escape.with_mods(Modifiers::ALT)),
+      (b"\x1b[A", Key::ArrowUp),
+      (b"\x1b[B", Key::ArrowDown),
+      (b"\x1b[C", Key::ArrowRight),
+      (b"\x1b[D", Key::ArrowLeft),
+      (b"\x1b[H", Key::Home),
+      (b"\x1b[F", Key::End),
+      (b"\x1b[Z", Key::BackTab),
+      (b"\x1b[P", Key::F1),
+      (b"\x1b[Q", Key::F2),
+      (b"\x1b[R", Key::F3),
+      (b"\x1b[S", Key::F4),
+      (b"\x1bOA", Key::ArrowUp),
+      (b"\x1bOB", Key::ArrowDown),
+      (b"\x1bOC", Key::ArrowRight),
+      (b"\x1bOD", Key::ArrowLeft),
+      (b"\x1bOH", Key::Home),
+      (b"\x1bOF", Key::End),
+      (b"\x1bOP", Key::F1),
+      (b"\x1bOQ", Key::F2),
+      (b"\x1bOR", Key::F3),
+      (b"\x1bOS", Key::F4),
+      (b"\x1bOM", Key::Enter),
+      (b"\x1b[1~", Key::Home),
+      (b"\x1b[2~", Key::Insert),
+      (b"\x1b[3~", Key::Delete),
+      (b"\x1b[4~", Key::End),
+      (b"\x1b[5~", Key::PageUp),
+      (b"\x1b[6~", Key::PageDown),
+      (b"\x1b[7~", Key::Home),
+      (b"\x1b[8~", Key::End),
+      (b"\x1b[11~", Key::F1),
+      (b"\x1b[12~", Key::F2),
+      (b"\x1b[13~", Key::F3),
+      (b"\x1b[14~", Key::F4),
+      (b"\x1b[15~", Key::F5),
+      (b"\x1b[17~", Key::F6),
+      (b"\x1b[18~", Key::F7),
+      (b"\x1b[19~", Key::F8),
+      (b"\x1b[20~", Key::F9),
+      (b"\x1b[21~", Key::F10),
+      (b"\x1b[23~", Key::F11),
+      (b"\x1b[24~", Key::F12),
+      (b"\x1b[[A", Key::F1),
+      (b"\x1b[[B", Key::F2),
+      (b"\x1b[[C", Key::F3),
+      (b"\x1b[[D", Key::F4),
+      (b"\x1b[[E", Key::F5)
+    ];
+    for (seq, key) in bytes {
+      t.insert(seq, key.clone());
+    }
+    #[cfg(feature = "curses")]
+    {
+      use pancurses::Input;
+      let curses = [
+        (Input::Character('\n'), Key::Enter),
+        (Input::Character('\r'), Key::Enter),
+        (Input::KeyEnter, Key::Enter),
+        (Input::Character('\t'), Key::Tab),
+        (Input::Character(' '), Key::Space),
+        (Input::Character('\x7f'), Key::Backspace),
+        (Input::Character('\x08'), Key::Backspace),
+        (Input::KeyBackspace, Key::Backspace),
+        (Input::Character('\x1b'), Key::Escape),
+        (Input::KeyUp, Key::ArrowUp),
+        (Input::KeyDown, Key::ArrowDown),
+        (Input::KeyLeft, Key::ArrowLeft),
+        (Input::KeyRight, Key::ArrowRight),
+        (Input::KeySR, Key::ArrowUp.with_mods(Modifiers::SHIFT)),
+        (Input::KeySF, Key::ArrowDown.with_mods(Modifiers::SHIFT)),
+        (Input::KeySLeft, Key::ArrowLeft.with_mods(Modifiers::SHIFT)),
+        (Input::KeySRight, Key::ArrowRight.with_mods(Modifiers::SHIFT)),
+        (Input::KeyHome, Key::Home),
+        (Input::KeyEnd, Key::End),
+        (Input::KeySHome, Key::Home.with_mods(Modifiers::SHIFT)),
+        (Input::KeySEnd, Key::End.with_mods(Modifiers::SHIFT)),
+        (Input::KeyPPage, Key::PageUp),
+        (Input::KeyNPage, Key::PageDown),
+        (Input::KeyDC, Key::Delete),
+        (Input::KeySDC, Key::Delete.with_mods(Modifiers::SHIFT)),
+        (Input::KeyIC, Key::Insert),
+        (Input::KeyBTab, Key::BackTab),
+        (Input::KeyF1, Key::F1),
+        (Input::KeyF2, Key::F2),
+        (Input::KeyF3, Key::F3),
+        (Input::KeyF4, Key::F4),
+        (Input::KeyF5, Key::F5),
+        (Input::KeyF6, Key::F6),
+        (Input::KeyF7, Key::F7),
+        (Input::KeyF8, Key::F8),
+        (Input::KeyF9, Key::F9),
+        (Input::KeyF10, Key::F10),
+        (Input::KeyF11, Key::F11),
+        (Input::KeyF12, Key::F12)
+      ];
+      for (input, key) in curses.iter() {
+        t.insert_curses(*input, key.clone());
+      }
+    }
+    t
+  }
+}
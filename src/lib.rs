@@ -1,92 +1,950 @@
+pub mod app;
+pub mod backend;
+mod buffer;
+pub mod builder;
 pub mod cli;
+pub mod complete;
+pub mod editor;
+pub mod error;
+pub mod history;
+pub mod input;
+pub mod keymap;
+pub mod keytable;
+pub mod prompt;
+pub mod secret;
+pub mod snapshot;
+mod width;
+
+pub use error::{Error, Result};
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use app::{App, Control};
+  use backend::{AnsiBackend, Backend, VirtualBackend};
+  use builder::{InputMode, Options, TerminalBuilder};
   use cli::*;
+  use complete::{Completer, PathCompleter, WordCompleter};
+  use history::History;
+  use input::ScriptedInput;
+  use keymap::{Action, Keymap};
+  use std::time::Duration;
+  use keytable::KeyTable;
+  use prompt::{AskOptions, NumberOptions, PasswordOptions, TextAreaOptions};
+
+  fn term(keys: Vec<Key>) -> Terminal<VirtualBackend> {
+    let b = VirtualBackend::new(20, 8);
+    b.feed(keys);
+    Terminal::with_backend(b)
+  }
+
+  fn snapshot(name: &str) -> String {
+    format!("{}/snapshots/{}.txt", env!("CARGO_MANIFEST_DIR"), name)
+  }
+
+  #[cfg(feature = "curses")]
   #[test]
-  fn term() {
+  fn curses_term() {
     Terminal::new();
   }
 
   #[test]
   fn output() {
-    let t = Terminal::new();
+    let t = term(vec![]);
     t.out("Hello ".into());
     t.outln(" World!".into());
     t.outbr();
     t.outln("This is a test.".into());
+    t.backend().assert_contents("Hello  World!\n\nThis is a test.");
   }
 
   #[test]
   fn input() {
-    let t = Terminal::new();
+    let t = term(vec![Key::char('h'), Key::char('i'), Key::Enter]);
     let a = t.ask("> ".into());
-    println!("{}", a);
+    assert_eq!(a, "hi");
+    t.backend().assert_contents("> hi");
   }
 
   #[test]
   fn delete() {
-    let t = Terminal::new();
+    let t = term(vec![]);
     t.out("Hello world!".into());
     t.raw_delete_to(5);
+    assert_eq!(t.backend().line(0), "Hello");
     t.raw_delete_offset(-2);
+    assert_eq!(t.backend().line(0), "Hel");
     t.raw_delete_prev();
-    t.get_char();
+    assert_eq!(t.backend().line(0), "He");
+    assert_eq!(t.raw_posxy(), (2, 0));
   }
 
   #[test]
   fn choices() {
-    let t = Terminal::new();
+    let t = term(vec![Key::ArrowDown, Key::ArrowDown, Key::Enter]);
     t.outln("Choose...".into());
     let x = t.choices("-> ".into(), vec!["c1".into(), "c22".into(), "c333".into(), "c4444".into()]);
+    assert_eq!(x, "c333");
     t.outln(x);
-    t.get_char();
+    t.backend().assert_snapshot(snapshot("choices"));
   }
 
   #[test]
   fn get_char() {
-    let t = Terminal::new();
+    let t = term(vec![Key::F3]);
     assert_eq!(t.get_char().unwrap(), Key::F3);
   }
 
   #[test]
   fn mask() {
-    let t = Terminal::new();
-    println!("{}", t.mask("> ".into(), '?'));
+    let t = term(vec![Key::char('p'), Key::char('w'), Key::Enter]);
+    assert_eq!(t.mask("> ".into(), '?'), "pw");
+    t.backend().assert_contents("> ??");
   }
 
   #[test]
   fn yes_no() {
-    let t = Terminal::new();
-    println!("{}", t.yesno("y/n".into(), true));
+    let t = term(vec![Key::ArrowRight, Key::Enter]);
+    assert!(!t.yesno("y/n".into(), true));
+    t.backend().assert_contents("(y/N)");
   }
 
   #[test]
   fn layer2d() {
-    let mut t = Terminal::new();
+    let mut t = term(vec![]);
     let mut l = Layer::new(0, 0);
     l.set_content("X".into());
     let l2d = Layer2D::new(0, 0, 5, 5, l);
-    let l2d = t.add_layer2d(l2d);
+    t.add_layer2d(l2d);
     t.refresh();
-    t.get_char();
+    t.backend().assert_snapshot(snapshot("layer2d"));
   }
 
   #[test]
   fn layer_arr() {
-    let mut t = Terminal::new();
+    let mut t = term(vec![]);
     let mut behindl = Layer::new(0, 0);
     behindl.set_content("X".into());
-    let mut behindl2d = Layer2D::new(0, 0, 4, 4, behindl);
+    let behindl2d = Layer2D::new(0, 0, 4, 4, behindl);
     t.add_layer2d(behindl2d);
     let mut frontl = Layer::new(0, 0);
     frontl.set_content("Y".into());
-    let mut frontl2d = Layer2D::new(0, 0, 2, 2, frontl);
+    let frontl2d = Layer2D::new(0, 0, 2, 2, frontl);
     t.add_layer2d(frontl2d);
     t.layer_swap(0, -1);
-    println!("{:?}", t.layer_back());
+    assert_eq!(t.layer_back().length, 2);
+    t.refresh();
+    t.backend().assert_snapshot(snapshot("layer_arr"));
+  }
+
+  #[test]
+  fn script() {
+    let events = ScriptedInput::parse("a <lt><Enter>\n<up><F12>").unwrap().remaining();
+    let keys = vec![Key::char('a'), Key::Space, Key::char('<'), Key::Enter, Key::Enter, Key::ArrowUp, Key::F12];
+    assert_eq!(events, keys.into_iter().map(Event::Key).collect::<Vec<_>>());
+    assert_eq!(ScriptedInput::parse("<Nope>").unwrap_err(), Error::InvalidKey("Nope".into()));
+    assert_eq!(ScriptedInput::parse("a<Enter").unwrap_err(), Error::InvalidKey("<Enter".into()));
+  }
+
+  #[test]
+  fn scripted_prompts() {
+    let mut t = term(vec![]);
+    t.set_input(ScriptedInput::parse("abd<Backspace>c<Enter>secret<Enter><Left><Enter><Down><Enter>").unwrap());
+    assert_eq!(t.try_ask("> ".into()), Ok("abc".into()));
+    assert_eq!(t.try_mask(" ".into(), '*'), Ok("secret".into()));
+    assert_eq!(t.try_yesno("y/n".into(), false), Ok(true));
+    assert_eq!(t.try_choices("> ".into(), vec!["a".into(), "b".into()]), Ok("b".into()));
+  }
+
+  #[test]
+  fn input_ended() {
+    let mut t = term(vec![]);
+    t.set_input(ScriptedInput::parse("abc").unwrap());
+    assert_eq!(t.try_ask("> ".into()), Err(Error::InputEnded));
+    assert_eq!(t.try_yesno("y/n".into(), true), Err(Error::InputEnded));
+    assert_eq!(t.ask("> ".into()), "");
+    t.reset_input();
+    t.backend().feed_script("ok<Enter>").unwrap();
+    assert_eq!(t.try_ask("> ".into()), Ok("ok".into()));
+  }
+
+  #[test]
+  fn ansi_output() {
+    let t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b""[..]));
+    t.outln("ab".into());
+    t.raw_move(3, 0);
+    t.raw_delete();
+    t.raw_move(1, 2);
+    assert_eq!(String::from_utf8_lossy(&t.backend().writer()), "\x1b[?2004hab\x1b[K\r\n\x1b[1A\x1b[4G\x1b[P\x1b[1B\r\n\x1b[2G");
+    assert_eq!(t.raw_posxy(), (1, 2));
+  }
+
+  #[test]
+  fn ansi_keys() {
+    let b = AnsiBackend::new(Vec::new(), "a\x1b[B\x1bOR\x1b[24~\x7f\r\x1bxé".as_bytes());
+    let keys: Vec<Event> = std::iter::from_fn(|| b.get_event()).collect();
+    let expected = vec![Key::char('a'), Key::ArrowDown, Key::F3, Key::F12, Key::Backspace, Key::Enter, Key::alt('x'), Key::char('é')];
+    assert_eq!(keys, expected.into_iter().map(Event::Key).collect::<Vec<_>>());
+    b.set_size(100, 30);
+    assert_eq!(b.get_event(), Some(Event::Resize(100, 30)));
+    assert_eq!(b.size(), (100, 30));
+  }
+
+  #[test]
+  fn extended_keys() {
+    let input = "\x1b[H\x1b[4~\x1b[5~\x1b[6~\x1b[3~\x1b[2~\t\x1b[Z\x03\x1b[1;5D\x1b[1;2A\x1b[15;3~\x1bOF\x1b[99X\x1b\x1b";
+    let b = AnsiBackend::new(Vec::new(), input.as_bytes());
+    let keys: Vec<Event> = std::iter::from_fn(|| b.get_event()).collect();
+    let expected = vec![
+      Key::Home, Key::End, Key::PageUp, Key::PageDown, Key::Delete, Key::Insert, Key::Tab, Key::BackTab, Key::ctrl('c'),
+      Key::ArrowLeft.with_mods(Modifiers::CTRL), Key::ArrowUp.with_mods(Modifiers::SHIFT), Key::F5.with_mods(Modifiers::ALT),
+      Key::End, Key::Escape.with_mods(Modifiers::ALT)
+    ];
+    assert_eq!(keys, expected.into_iter().map(Event::Key).collect::<Vec<_>>());
+
+    let keys = ScriptedInput::parse("<Esc><Tab><C-x><A-Left><S-F3><PgDn><C-S>").unwrap().remaining();
+    let expected = vec![
+      Key::Escape, Key::Tab, Key::ctrl('x'), Key::ArrowLeft.with_mods(Modifiers::ALT), Key::F3.with_mods(Modifiers::SHIFT),
+      Key::PageDown, Key::ctrl('s')
+    ];
+    assert_eq!(keys, expected.into_iter().map(Event::Key).collect::<Vec<_>>());
+    assert_eq!("X-a".parse::<Key>(), Err(Error::InvalidKey("X-a".into())));
+
+    let t = term(vec![Key::ctrl('a'), Key::alt('b'), Key::char('c'), Key::Home, Key::Enter]);
+    assert_eq!(t.try_ask("> ".into()), Ok("c".into()));
+  }
+
+  #[test]
+  fn key_table() {
+    let table = KeyTable::default();
+    let cases: &[(&[u8], Key)] = &[
+      (b"\x7f", Key::Backspace),
+      (b"\x08", Key::Backspace),
+      (b" ", Key::Space),
+      (b"\r", Key::Enter),
+      (b"\x1bOM", Key::Enter),
+      (b"\x1b", Key::Escape),
+      (b"\x1b[[C", Key::F3),
+      (b"\x1b[7~", Key::Home),
+      (b"\x1b[5;5~", Key::PageUp.with_mods(Modifiers::CTRL)),
+      (b"\x1b[1;6C", Key::ArrowRight.with_mods(Modifiers::CTRL | Modifiers::SHIFT)),
+      (b"\x1b[1;9A", Key::ArrowUp.with_mods(Modifiers::ALT)),
+      (b"\x1bf", Key::alt('f')),
+      (b"\x1b\x01", Key::ctrl('a').with_mods(Modifiers::ALT)),
+      (b"\x1f", Key::ctrl('_')),
+      (b"\x00", Key::ctrl(' ')),
+      ("ß".as_bytes(), Key::char('ß'))
+    ];
+    for (bytes, key) in cases {
+      assert_eq!(table.decode(bytes).as_ref(), Some(key), "{:?}", bytes);
+    }
+    assert_eq!(table.decode(b"\x1b[99~"), None);
+    assert_eq!(table.decode(b"\x1bOX"), None);
+    assert_eq!(table.decode(b"ab"), None);
+
+    #[cfg(feature = "curses")]
+    {
+      use pancurses::Input;
+      assert_eq!(table.decode_curses(Input::KeyBackspace), Some(Key::Backspace));
+      assert_eq!(table.decode_curses(Input::Character('\x7f')), Some(Key::Backspace));
+      assert_eq!(table.decode_curses(Input::Character(' ')), Some(Key::Space));
+      assert_eq!(table.decode_curses(Input::Character('\x17')), Some(Key::ctrl('w')));
+      assert_eq!(table.decode_curses(Input::KeySLeft), Some(Key::ArrowLeft.with_mods(Modifiers::SHIFT)));
+      assert_eq!(table.decode_curses(Input::KeyCancel), None);
+      assert_eq!(table.decode_curses_seq(&[Input::Character('\x1b'), Input::Character('b')]), Some(Key::char('b').with_mods(Modifiers::ALT)));
+      assert_eq!(table.decode_curses_seq(&[Input::Character('\x1b'), Input::Character('\r')]), Some(Key::Enter.with_mods(Modifiers::ALT)));
+      assert_eq!(table.decode_curses_seq(&[Input::Character('\x1b'), Input::KeyCancel]), None);
+      assert_eq!(table.decode_curses_seq(&[Input::Character('\x1b')]), Some(Key::Escape));
+      assert_eq!(table.decode_curses_name("kLFT5"), Some(Key::ArrowLeft.with_mods(Modifiers::CTRL)));
+      assert_eq!(table.decode_curses_name("kRIT3"), Some(Key::ArrowRight.with_mods(Modifiers::ALT)));
+      assert_eq!(table.decode_curses_name("kDC6"), Some(Key::Delete.with_mods(Modifiers { ctrl: true, alt: false, shift: true })));
+      assert_eq!(table.decode_curses_name("kUP9"), None);
+      assert_eq!(table.decode_curses_name("kxIN"), None);
+    }
+
+    let mut custom = KeyTable::default();
+    assert_eq!(custom.insert(b"\x08", Key::ctrl('h')), Some(Key::Backspace));
+    custom.insert(b"\x1b[200~", Key::F12);
+    custom.remove(b" ");
+    let t = TerminalBuilder::new().key_table(custom).bracketed_paste(false).build_with(AnsiBackend::new(Vec::new(), &b"\x08\x1b[200~ \x1b[200;2~"[..]));
+    let keys: Vec<Key> = std::iter::from_fn(|| t.get_char_hidden()).collect();
+    assert_eq!(keys, vec![Key::ctrl('h'), Key::F12, Key::char(' '), Key::F12.with_mods(Modifiers::SHIFT)]);
+
+    let t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b"a b\x7f\x7fc\r"[..]));
+    assert_eq!(t.try_ask("".into()), Ok("ac".into()));
+  }
+
+  #[test]
+  fn ansi_prompt() {
+    let t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b"hj\x7fi\r\x1b[C\r"[..]));
+    assert_eq!(t.try_ask("> ".into()), Ok("hi".into()));
+    assert_eq!(t.try_yesno("y/n".into(), true), Ok(false));
+    assert_eq!(t.try_ask("> ".into()), Err(Error::InputEnded));
+  }
+
+  #[test]
+  fn ansi_flushes_before_reading() {
+    use std::cell::{Cell, RefCell};
+    use std::io::{Read, Write};
+    use std::rc::Rc;
+
+    // counts the bytes written since the last flush, which the reader records at every read
+    struct Writer(Rc<Cell<usize>>);
+    impl Write for Writer {
+      fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.set(self.0.get() + buf.len());
+        Ok(buf.len())
+      }
+      fn flush(&mut self) -> std::io::Result<()> {
+        self.0.set(0);
+        Ok(())
+      }
+    }
+    struct Reader(&'static [u8], Rc<Cell<usize>>, Rc<RefCell<Vec<usize>>>);
+    impl Read for Reader {
+      fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.2.borrow_mut().push(self.1.get());
+        self.0.read(buf)
+      }
+    }
+
+    let unflushed = Rc::new(Cell::new(0));
+    let reads = Rc::new(RefCell::new(vec![]));
+    let reader = Reader(b"abc\r", unflushed.clone(), reads.clone());
+    let t = Terminal::with_backend(AnsiBackend::new(Writer(unflushed), reader));
+    assert_eq!(t.try_ask("> ".into()), Ok("abc".into()));
+    assert_eq!(*reads.borrow(), vec![0, 0, 0, 0]);
+  }
+
+  #[test]
+  fn diff_refresh() {
+    let mut t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b""[..]));
+    let mut l = Layer::new(0, 0);
+    l.set_content("ab".into());
+    t.add_layer2d(Layer2D::new(1, 1, 3, 2, l));
+    t.refresh();
+    let drawn = t.backend().writer().len();
+    t.refresh();
+    assert_eq!(t.backend().writer().len(), drawn);
+    t.layer_front_mut().index(2, 1).set_content("cd".into());
+    t.refresh();
+    assert_eq!(String::from_utf8_lossy(&t.backend().writer()[drawn..]), "\x1b[2B\x1b[6Gcd\x1b[2A\x1b[1G");
+  }
+
+  #[test]
+  fn refresh_after_scrolling() {
+    let mut t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b""[..]));
+    for i in 0..30 {
+      t.outln(i.to_string());
+    }
+    let drawn = t.backend().writer().len();
+    let mut l = Layer::new(0, 0);
+    l.set_content("gone".into());
+    t.add_layer2d(Layer2D::new(0, 2, 1, 1, l.clone()));
+    l.set_content("here".into());
+    t.add_layer2d(Layer2D::new(0, 28, 1, 1, l));
+    t.refresh();
+    assert_eq!(String::from_utf8_lossy(&t.backend().writer()[drawn..]), "\x1b[2A\x1b[1Ghere\x1b[2B\x1b[1G");
+  }
+
+  #[test]
+  fn refresh_clears_stale() {
+    let mut t = term(vec![]);
+    let mut l = Layer::new(0, 0);
+    l.set_content("X".into());
+    t.add_layer2d(Layer2D::new(0, 0, 3, 1, l.clone()));
+    t.add_layer(Layer::new(2, 1)).set_content("hi".into());
+    t.refresh();
+    t.backend().assert_contents("XXX\n  hi");
+    t.layer_pop();
     t.refresh();
+    t.backend().assert_contents("XXX");
+    t.out("over".into());
+    t.backend().assert_contents("over");
+    t.redraw();
+    t.backend().assert_contents("XXXr");
+  }
+
+  #[test]
+  fn fallible() {
+    let mut t = term(vec![Key::Enter]);
+    assert_eq!(t.try_yesno("yes".into(), true), Err(Error::InvalidSuffix("yes".into())));
+    assert_eq!(t.try_choices("> ".into(), vec![]), Err(Error::NoChoices));
+    assert_eq!(t.try_layer_front().unwrap_err(), Error::EmptyLayerStack);
+    assert_eq!(t.try_layer_back().unwrap_err(), Error::EmptyLayerStack);
+    assert_eq!(t.try_layer_locate(0).unwrap_err(), Error::LayerNotFound(0));
+    assert_eq!(t.try_layer_swap(0, -1), Err(Error::LayerNotFound(0)));
+    let l2d = t.add_layer2d(Layer2D::new(0, 0, 2, 3, Layer::new(0, 0)));
+    assert!(l2d.try_index(1, 2).is_ok());
+    assert_eq!(l2d.try_index(2, 0).unwrap_err(), Error::OutOfBounds(2, 0));
+    assert_eq!(l2d.try_get(0, 3).unwrap_err(), Error::OutOfBounds(0, 3));
+    assert!(t.try_layer_locate(0).is_ok());
+    assert_eq!(t.try_layer_locate(-1).unwrap_err(), Error::LayerNotFound(-1));
+    assert_eq!(t.try_layer_locate(1).unwrap_err(), Error::LayerNotFound(1));
+    t.out("ab".into());
+    t.raw_delete_offset(0);
+    t.raw_delete_to(-5);
+    t.backend().assert_contents("");
+  }
+
+  #[test]
+  #[should_panic(expected = "the layer stack is empty")]
+  fn layer_front_panics() {
+    term(vec![]).layer_front();
+  }
+
+  #[test]
+  fn suspend_resume() {
+    let mut t = term(vec![]);
+    t.add_layer(Layer::new(1, 0)).set_content("on".into());
+    t.refresh();
+    t.suspend();
+    assert!(t.backend().is_suspended());
+    t.backend().clear();
+    t.resume();
+    assert!(!t.backend().is_suspended());
+    t.backend().assert_contents(" on");
+  }
+
+  #[test]
+  fn builder() {
+    let b = VirtualBackend::new(10, 2);
+    b.feed_script("ab").unwrap();
+    let t = TerminalBuilder::new().input_mode(InputMode::Raw).nodelay(true).echo(false).build_with(b);
+    assert_eq!(t.backend().options().input_mode, InputMode::Raw);
+    assert!(t.backend().options().nodelay);
+    assert_eq!(t.get_char(), Some(Key::char('a')));
+    t.backend().assert_contents("");
+    let t = Terminal::with_backend(VirtualBackend::new(10, 2));
+    assert_eq!(t.backend().options().input_mode, InputMode::Cbreak);
+    t.backend().feed_script("ab").unwrap();
     t.get_char();
+    t.backend().assert_contents("a");
+  }
+
+  #[test]
+  fn ansi_options() {
+    let t = TerminalBuilder::new().cursor_visible(false).alternate_screen(true).build_with(AnsiBackend::new(Vec::new(), &b""[..]));
+    t.out("x".into());
+    t.suspend();
+    t.resume();
+    assert_eq!(String::from_utf8_lossy(&t.backend().writer()), "\x1b[?1049h\x1b[H\x1b[?25l\x1b[?2004hx\x1b[?25h\x1b[?1049l\x1b[?2004l\x1b[?1049h\x1b[H\x1b[?25l\x1b[?2004h");
+  }
+
+  #[test]
+  fn resize() {
+    let mut t = term(vec![]);
+    let mut l = Layer::new(0, 0);
+    l.set_content("#".into());
+    t.add_layer2d(Layer2D::new(0, 0, 3, 2, l.clone()));
+    t.add_layer2d(Layer2D::new(1, 0, 2, 1, l.clone())).set_anchor(Anchor::BottomRight);
+    t.add_layer2d(Layer2D::new(0, 0, 2, 1, l)).set_anchor(Anchor::Center);
+    t.refresh();
+    t.backend().assert_contents("###\n###\n\n         ##\n\n\n\n                 ##");
+    t.backend().resize(12, 4);
+    t.backend().feed(vec![Key::char('a')]);
+    assert_eq!(t.get_char(), Some(Key::char('a')));
+    t.backend().assert_contents("a##\n###  ##\n\n         ##");
+    t.backend().resize(6, 3);
+    t.backend().feed(vec![Key::Enter]);
+    assert_eq!(t.get_event(), Some(Event::Resize(6, 3)));
+    t.backend().assert_contents("###\n####\n   ##");
+  }
+
+  #[test]
+  fn wide_layers() {
+    let mut t = term(vec![]);
+    let mut l = Layer::new(0, 0);
+    l.set_content("cafe\u{301} 字😀|".into());
+    assert_eq!(l.length(), 10);
+    t.add_layer(l);
+    t.refresh();
+    assert_eq!(t.backend().line(0), "cafe\u{301} 字😀|");
+    assert_eq!(t.backend().cell(6, 0), Some("".into()));
+    t.layer_front_mut().index(0, 0).set_content("字".into());
+    t.refresh();
+    t.backend().assert_contents("字");
+    t.layer_front_mut().index(0, 0).shrink();
+    assert_eq!(t.layer_front().get(0, 0).length(), 2);
+
+    let mut cell = Layer::new(0, 0);
+    cell.set_content("字".into());
+    t.add_layer2d(Layer2D::new(0, 1, 3, 1, cell));
+    t.layer_front_mut().index(1, 0).set_content("é".into());
+    t.layer_front_mut().index(2, 0).set_content("ab".into());
+    t.refresh();
+    assert_eq!(t.backend().line(1), "字é ab");
+    t.layer_front_mut().index(0, 0).set_content("xyz".into());
+    t.refresh();
+    assert_eq!(t.backend().line(1), "xyé ab");
+    t.layer_front_mut().index(0, 0).set_content("x字".into());
+    t.refresh();
+    assert_eq!(t.backend().line(1), "x é ab");
+  }
+
+  #[test]
+  fn mouse() {
+    let input = "\x1b[<0;5;3M\x1b[<32;6;3M\x1b[<0;6;3m\x1b[<65;1;1M\x1b[<18;2;2M\x1b[<35;1;1M\x1b[M #!a";
+    let b = AnsiBackend::new(Vec::new(), input.as_bytes());
+    b.configure(&Options { mouse: true, bracketed_paste: false, ..Options::default() });
+    assert_eq!(String::from_utf8_lossy(&b.writer()), "\x1b[?1000h\x1b[?1002h\x1b[?1006h");
+    let events: Vec<Event> = std::iter::from_fn(|| b.get_event()).collect();
+    let ctrl_right = MouseEvent { mods: Modifiers::CTRL, ..MouseEvent::new(MouseKind::Press(MouseButton::Right), 1, 1) };
+    assert_eq!(events, vec![
+      Event::Mouse(MouseEvent::new(MouseKind::Press(MouseButton::Left), 4, 2)),
+      Event::Mouse(MouseEvent::new(MouseKind::Drag(MouseButton::Left), 5, 2)),
+      Event::Mouse(MouseEvent::new(MouseKind::Release(MouseButton::Left), 5, 2)),
+      Event::Mouse(MouseEvent::new(MouseKind::ScrollDown, 0, 0)),
+      Event::Mouse(ctrl_right),
+      Event::Mouse(MouseEvent::new(MouseKind::Press(MouseButton::Left), 2, 0)),
+      Event::Key(Key::char('a'))
+    ]);
+
+    let mut t = term(vec![]);
+    let mut l = Layer::new(0, 0);
+    l.set_content("ab".into());
+    t.add_layer2d(Layer2D::new(1, 1, 3, 2, l.clone())).index(2, 1).set_content("xyz".into());
+    t.add_layer2d(Layer2D::new(0, 0, 1, 1, l)).set_anchor(Anchor::Center);
+    assert!(t.layer_at(0, 0).is_none());
+    let (l2d, cell) = t.layer_at(6, 2).unwrap();
+    assert_eq!((l2d.length, cell.get_content().as_str(), cell.posx), (3, "xyz", 4));
+    let (l2d, cell) = t.layer_at(9, 3).unwrap();
+    assert_eq!((l2d.anchor, cell.posx), (Anchor::Center, 0));
+    assert!(t.layer_at(11, 3).is_none());
+
+    let t = term(vec![]);
+    let click = |x, y| Event::Mouse(MouseEvent::new(MouseKind::Press(MouseButton::Left), x, y));
+    t.out("ok? ".into());
+    t.backend().feed_event(click(4, 0));
+    t.backend().feed_event(click(10, 0));
+    assert_eq!(t.try_yesno("yes/no".into(), true), Ok(false));
+    t.backend().assert_contents("ok? (yes/NO)");
+    t.outbr();
+    t.backend().feed_event(click(0, 0));
+    t.backend().feed_event(click(0, 4));
+    assert_eq!(t.try_choices("> ".into(), vec!["a".into(), "b".into(), "c".into()]), Ok("c".into()));
+    t.backend().assert_contents("ok? (yes/NO)\n\na\nb\n> c");
+  }
+
+  #[test]
+  fn keymap() {
+    let keymap = Keymap::parse("# vim\nj = Next\n<k> = prev\n\nq = Cancel\nEnter = none\nC-m = Submit").unwrap();
+    assert_eq!(keymap.action(&Key::char('j')), Some(Action::Next));
+    assert_eq!(keymap.action(&Key::char('k')), Some(Action::Prev));
+    assert_eq!(keymap.action(&Key::Enter), None);
+    assert_eq!(keymap.keys(Action::Submit), vec![Key::ctrl('m')]);
+    assert_eq!(Keymap::parse("j Next"), Err(Error::InvalidBinding(1, "j Next".into())));
+    assert_eq!(Keymap::parse("\nj = Nxt"), Err(Error::InvalidBinding(2, "j = Nxt".into())));
+    assert!(matches!(Keymap::load("/nonexistent/keys.conf"), Err(Error::Io(_))));
+
+    let b = VirtualBackend::new(20, 8);
+    b.feed(vec![Key::char('j'), Key::char('j'), Key::char('k'), Key::ctrl('m')]);
+    let mut t = TerminalBuilder::new().keymap(keymap.clone()).build_with(b);
+    assert_eq!(t.choices("> ".into(), vec!["a".into(), "b".into(), "c".into()]), "b");
+    // keys bound to actions a prompt does not use are typed
+    t.backend().feed(vec![Key::char('j'), Key::Enter, Key::ctrl('m')]);
+    assert_eq!(t.try_ask("".into()), Ok("j".into()));
+    t.backend().feed(vec![Key::char('a'), Key::char('q')]);
+    assert_eq!(t.try_ask("".into()), Err(Error::Cancelled));
+
+    t.set_keymap(Keymap::default());
+    t.backend().feed(vec![Key::Space, Key::Escape]);
+    assert_eq!(t.try_yesno("y/n".into(), true), Err(Error::Cancelled));
+    t.backend().feed(vec![Key::Space, Key::Escape]);
+    assert!(t.yesno("y/n".into(), true));
+    t.backend().feed(vec![Key::Space, Key::Enter]);
+    assert!(!t.yesno("y/n".into(), true));
+  }
+
+  #[test]
+  fn timeouts() {
+    let t = term(vec![Key::char('a')]);
+    t.backend().resize(20, 6);
+    let wait = Duration::from_millis(10);
+    assert_eq!(t.get_char_timeout(wait), Ok(Some(Key::char('a'))));
+    assert_eq!(t.get_char_timeout(wait), Ok(None));
+    assert_eq!(t.poll_key(), Ok(None));
+    t.backend().feed(vec![Key::F1]);
+    assert_eq!(t.poll_key(), Ok(Some(Key::F1)));
+    t.backend().assert_contents("a");
+
+    t.set_prompt_timeout(Some(wait));
+    t.backend().feed(vec![Key::char('b')]);
+    assert_eq!(t.try_ask("".into()), Err(Error::TimedOut));
+    assert!(t.yesno("y/n".into(), true));
+    t.set_prompt_timeout(None);
+    assert_eq!(t.try_ask("".into()), Err(Error::InputEnded));
+
+    let mut t = TerminalBuilder::new().prompt_timeout(wait).build_with(VirtualBackend::new(20, 8));
+    assert_eq!(t.try_choices("".into(), vec!["a".into()]), Err(Error::TimedOut));
+    t.set_input(ScriptedInput::new(vec![]));
+    assert_eq!(t.get_event_timeout(wait), Err(Error::InputEnded));
+
+    let b = AnsiBackend::new(Vec::new(), &b"x"[..]);
+    assert_eq!(b.get_event_timeout(wait), Ok(Some(Event::Key(Key::char('x')))));
+    assert_eq!(b.get_event_timeout(wait), Err(Error::InputEnded));
+
+    // without waiting for keys, none being ready is not the end of input
+    let mut t = TerminalBuilder::new().nodelay(true).build_with(VirtualBackend::new(20, 8));
+    t.backend().feed(vec![Key::char('a')]);
+    assert_eq!(t.try_ask("".into()), Err(Error::TimedOut));
+    t.set_input(ScriptedInput::new(vec![]));
+    assert_eq!(t.try_ask("".into()), Err(Error::InputEnded));
+    let t = TerminalBuilder::new().nodelay(true).build_with(AnsiBackend::new(Vec::new(), &b"b"[..]));
+    assert_eq!(t.try_ask("".into()), Err(Error::InputEnded));
+  }
+
+  struct Counter {
+    events: Vec<Event>,
+    ticks: Option<Duration>
+  }
+
+  impl App<VirtualBackend> for Counter {
+    fn handle_event(&mut self, event: Event) -> Control {
+      self.events.push(event.clone());
+      match event {
+        Event::Key(Key::Char { c: 'q', .. }) => Control::Quit,
+        Event::Tick if self.events.len() == 3 => Control::Quit,
+        Event::Key(_) | Event::Tick => Control::Redraw,
+        _ => Control::Continue
+      }
+    }
+
+    fn draw(&self, t: &mut Terminal<VirtualBackend>) {
+      t.layer_front_mut().index(0, 0).set_content(format!("{} events", self.events.len()));
+    }
+
+    fn tick_rate(&self) -> Option<Duration> {
+      self.ticks
+    }
+  }
+
+  #[test]
+  fn run() {
+    let mut t = term(vec![Key::char('a'), Key::char('b')]);
+    t.add_layer2d(Layer2D::new(0, 0, 1, 1, Layer::new(0, 0)));
+    t.backend().feed_event(Event::Resize(20, 8));
+    t.backend().feed(vec![Key::char('q'), Key::char('x')]);
+    let mut app = Counter { events: vec![], ticks: None };
+    assert_eq!(t.try_run(&mut app), Ok(()));
+    assert_eq!(app.events.len(), 4);
+    // the resize did not ask for a redraw
+    t.backend().assert_contents("2 events");
+    assert_eq!(t.try_run(&mut app), Err(Error::InputEnded));
+    t.backend().assert_contents("5 events");
+
+    // the virtual backend times out straight away once its keys run out
+    let mut app = Counter { events: vec![], ticks: Some(Duration::from_millis(1)) };
+    t.backend().feed(vec![Key::char('a')]);
+    t.run(&mut app);
+    assert_eq!(app.events, vec![Event::Key(Key::char('a')), Event::Tick, Event::Tick]);
+    t.backend().assert_contents("2 events");
+  }
+
+  #[test]
+  fn paste() {
+    let input = "a\x1b[200~tok\r\nen\x1b[201~\x1b[200~\x1b[A\r\x1b[201~b\x1b[200~cut";
+    let b = AnsiBackend::new(Vec::new(), input.as_bytes());
+    b.configure(&Options::default());
+    assert_eq!(String::from_utf8_lossy(&b.writer()), "\x1b[?2004h");
+    let events: Vec<Event> = std::iter::from_fn(|| b.get_event()).collect();
+    assert_eq!(events, vec![
+      Event::Key(Key::char('a')),
+      Event::Paste("tok\nen".into()),
+      Event::Paste("\x1b[A\n".into()),
+      Event::Key(Key::char('b')),
+      Event::Paste("cut".into())
+    ]);
+
+    // newlines in a paste do not submit the prompt
+    let t = term(vec![Key::char('>')]);
+    t.backend().feed_event(Event::Paste("api\nkey\tid\x07\n".into()));
+    t.backend().feed(vec![Key::Enter]);
+    assert_eq!(t.try_ask("".into()), Ok(">api key\tid".into()));
+    t.backend().feed_event(Event::Paste("pw\n".into()));
+    t.backend().feed(vec![Key::char('d'), Key::Enter]);
+    assert_eq!(t.try_mask("".into(), '*'), Ok("pwd".into()));
+  }
+
+  #[test]
+  fn line_editing() {
+    let mut t = term(vec![]);
+    t.set_input(ScriptedInput::parse("helo<Left>l<End>!\n").unwrap());
+    assert_eq!(t.try_ask("> ".into()), Ok("hello!".into()));
+    t.set_input(ScriptedInput::parse("one two three<C-w><C-w>four<A-b><C-k>x\n").unwrap());
+    assert_eq!(t.try_ask("".into()), Ok("one x".into()));
+    t.set_input(ScriptedInput::parse("abc<Home><Del><C-e>d<C-a><C-f><C-u>z<C-Right> e<A-Backspace>\n").unwrap());
+    assert_eq!(t.try_ask("".into()), Ok("zcd ".into()));
+    t.outbr();
+    t.set_input(ScriptedInput::parse("字a<Left><Left>b").unwrap());
+    assert_eq!(t.try_ask("".into()), Err(Error::InputEnded));
+    assert_eq!(t.raw_posxy(), (1, 1));
+    t.set_input(ScriptedInput::parse("pw<Left>x<Enter>").unwrap());
+    assert_eq!(t.try_mask("".into(), '*'), Ok("pxw".into()));
+    t.backend().assert_contents("> hello!one xzcd\nb***");
+  }
+
+  #[test]
+  fn history() {
+    let mut t = Terminal::with_backend(VirtualBackend::new(40, 20));
+    t.set_history(Some(History::new(3)));
+    let ask = |script: &str| {
+      t.backend().feed_script(script).unwrap();
+      let answer = t.try_ask("".into());
+      t.outbr();
+      answer
+    };
+    for answer in ["ls", "", "cd /", "ls", "git log"].iter() {
+      assert_eq!(ask(&format!("{}\n", answer)), Ok(answer.to_string()));
+    }
+    assert_eq!(ask("pw\n"), Ok("pw".into()));
+    t.backend().feed_script("hidden\n").unwrap();
+    t.try_mask("".into(), '*').unwrap();
+    let entries = |t: &Terminal<VirtualBackend>| t.history().as_ref().unwrap().iter().map(String::from).collect::<Vec<_>>();
+    assert_eq!(entries(&t), vec!["ls", "git log", "pw"]);
+
+    assert_eq!(ask("x<Up><Up><Up><Up><Down>\n"), Ok("git log".into()));
+    assert_eq!(ask("x<Up><C-p><Down><C-n><Down>y\n"), Ok("xy".into()));
+    assert_eq!(ask("<C-r>l\n"), Ok("git log".into()));
+    assert_eq!(ask("<C-r>g<C-r><C-r>z<Backspace><Backspace><Left>!\n"), Ok("git lo!g".into()));
+    assert_eq!(ask("a<C-r>git<Esc>b\n"), Ok("ab".into()));
+    t.backend().clear();
+    t.backend().feed_script("<C-r>b").unwrap();
+    assert_eq!(t.try_ask("> ".into()), Err(Error::InputEnded));
+    t.backend().assert_contents("> (reverse-i-search)`b': ab");
+
+    let path = std::env::temp_dir().join(format!("utile_cli_history_{}", std::process::id()));
+    let mut h = History::load(&path, 2).unwrap();
+    h.push("a").unwrap();
+    h.push("b").unwrap();
+    h.push("a").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "b\na\n");
+    assert_eq!(History::load(&path, 1).unwrap().iter().collect::<Vec<_>>(), vec!["a"]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(History::load(&path, 1).unwrap().is_empty());
+  }
+
+  #[test]
+  fn completion() {
+    let t = term(vec![]);
+    let options = AskOptions::new().completer(WordCompleter::new(vec!["status", "stash", "stop", "log"]));
+    t.backend().feed_script("git s<Tab>").unwrap();
+    t.backend().feed_script("<Tab>").unwrap();
+    assert_eq!(t.try_ask_with("".into(), &options), Err(Error::InputEnded));
+    t.backend().assert_contents("git st\nstatus  stash\nstop");
+    // the next key closes the list
+    t.backend().feed_script("<BS><Tab><Tab>sto<Tab>\n").unwrap();
+    assert_eq!(t.try_ask_with("".into(), &options), Ok("stop".into()));
+    t.backend().assert_contents("git ststop");
+    // a start in the middle of a character
+    let t = term(vec![]);
+    t.backend().feed_script("é<Tab>\n").unwrap();
+    let options = AskOptions::new().completer(|_: &str, _: usize| (1, vec!["e".to_string()]));
+    assert_eq!(t.try_ask_with("".into(), &options), Ok("é".into()));
+    // after the output scrolled
+    let t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b"s\t\t"[..]));
+    for i in 0..30 {
+      t.outln(i.to_string());
+    }
+    t.raw_move(0, 25);
+    let options = AskOptions::new().completer(WordCompleter::new(vec!["status", "stash"]));
+    assert_eq!(t.try_ask_with("".into(), &options), Err(Error::InputEnded));
+    assert!(String::from_utf8_lossy(&t.backend().writer()).contains("\x1b[1B\x1b[1G        \x1b[1Gstatus\x1b[9G        \x1b[9Gstash"));
+
+    let dir = std::env::temp_dir().join(format!("utile_cli_complete_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    for file in ["src/lib.rs", "src/list.rs", ".hidden", "Cargo.toml"].iter() {
+      std::fs::write(dir.join(file), "").unwrap();
+    }
+    let paths = PathCompleter::in_dir(&dir);
+    assert_eq!(paths.complete("cat ", 4), (4, vec!["Cargo.toml".into(), "src/".into()]));
+    assert_eq!(paths.complete("cat .", 5), (4, vec![".hidden".into()]));
+    assert_eq!(paths.complete("cat src/li x", 10), (4, vec!["src/lib.rs".into(), "src/list.rs".into()]));
+    assert_eq!(paths.complete("cat nope/", 9), (4, vec![]));
+    let t = term(vec![]);
+    t.backend().feed_script("s<Tab>l<Tab>s<Tab>\n").unwrap();
+    assert_eq!(t.try_ask_with("".into(), &AskOptions::new().completer(paths)), Ok("src/list.rs".into()));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn validation() {
+    let options = AskOptions::new().validator(|s| match s.chars().all(|c| c.is_ascii_digit()) {
+      true => Ok(()),
+      false => Err("digits only".to_string())
+    });
+    let t = term(vec![]);
+    t.backend().feed_script("12a\n<Left>").unwrap();
+    assert_eq!(t.try_ask_with("> ".into(), &options), Err(Error::InputEnded));
+    t.backend().assert_contents("> 12a\ndigits only");
+    let mut t = term(vec![]);
+    t.set_history(Some(History::default()));
+    t.backend().feed_script("12a\n\n<BS>\n").unwrap();
+    assert_eq!(t.try_ask_with("> ".into(), &options), Ok("12".into()));
+    t.backend().assert_contents("> 12");
+    assert_eq!(t.history().as_ref().unwrap().len(), 1);
+    // on the last row the error follows the line
+    let t = term(vec![]);
+    t.raw_move(0, 7);
+    t.backend().feed_script("12a\n").unwrap();
+    assert_eq!(t.try_ask_with("> ".into(), &options), Err(Error::InputEnded));
+    assert_eq!(t.backend().contents(), "\n\n\n\n\n\n\n> 12a digits only");
+    let t = term(vec![]);
+    t.raw_move(0, 7);
+    t.backend().feed_script("12a\n<BS>4\n").unwrap();
+    assert_eq!(t.try_ask_with("> ".into(), &options), Ok("124".into()));
+    assert_eq!(t.backend().contents(), "\n\n\n\n\n\n\n> 124");
+    // unless the output scrolls
+    let t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b"12a\r"[..]));
+    for i in 0..30 {
+      t.outln(i.to_string());
+    }
+    assert_eq!(t.try_ask_with("> ".into(), &options), Err(Error::InputEnded));
+    assert!(String::from_utf8_lossy(&t.backend().writer()).ends_with("12a\r\n\x1b[1G\x1b[1A\x1b[6G\x1b[1B\x1b[1G           \x1b[1Gdigits only\x1b[1A\x1b[6G"));
+  }
+
+  #[test]
+  fn typed_prompts() {
+    let t = term(vec![]);
+    t.backend().feed_script("x\n<BS>-3\n").unwrap();
+    assert_eq!(t.try_ask_parse::<i32>("> ".into()), Ok(-3));
+    t.backend().assert_contents("> -3");
+    let t = Terminal::with_backend(VirtualBackend::new(40, 5));
+    t.backend().feed_script("1.5.\n").unwrap();
+    assert_eq!(t.try_ask_parse::<f64>("> ".into()), Err(Error::InputEnded));
+    t.backend().assert_contents("> 1.5.\ninvalid float literal");
+
+    let options = NumberOptions::new().min(-2).max(10).step(4);
+    let t = term(vec![]);
+    t.backend().feed_script("<Down><Up><Up><Up><Up>").unwrap();
+    assert_eq!(t.try_ask_number("n: ".into(), &options), Err(Error::InputEnded));
+    t.backend().assert_contents("n: 10");
+    let t = term(vec![]);
+    t.backend().feed_script("12\n").unwrap();
+    assert_eq!(t.try_ask_number("n: ".into(), &options), Err(Error::InputEnded));
+    t.backend().assert_contents("n: 12\nmust be at most 10");
+    let t = term(vec![]);
+    t.backend().feed_script("12<Down><Down><Down><Down>\n").unwrap();
+    assert_eq!(t.try_ask_number("n: ".into(), &options), Ok(-2));
+    let t = term(vec![]);
+    t.backend().feed_script("ten\n").unwrap();
+    assert_eq!(t.ask_number("n: ".into(), &options), 0);
+    t.backend().assert_contents("n: ten\nnot a number");
+    let t = term(vec![]);
+    t.backend().feed_script("254<Up><Up>\n").unwrap();
+    assert_eq!(t.ask_number::<u8>("n: ".into(), &NumberOptions::new()), 255);
+    // ranges without zero start from their nearest bound
+    let options = NumberOptions::new().min(5.0).max(9.0);
+    let t = term(vec![]);
+    t.backend().feed_script("NaN\n").unwrap();
+    assert_eq!(t.ask_number("n: ".into(), &options), 5.0);
+    t.backend().assert_contents("n: NaN\nnot a number");
+    let t = term(vec![]);
+    t.backend().feed_script("<Up>\n").unwrap();
+    assert_eq!(t.try_ask_number("n: ".into(), &options), Ok(5.0));
+    let t = term(vec![]);
+    t.backend().feed_script("<Down>\n").unwrap();
+    assert_eq!(t.try_ask_number("n: ".into(), &NumberOptions::new().max(-3)), Ok(-3));
+  }
+
+  #[test]
+  fn prompt_defaults() {
+    let t = term(vec![]);
+    t.backend().feed_script("<BS>1\n").unwrap();
+    assert_eq!(t.ask_with("> ".into(), &AskOptions::new().initial("v1.0".into())), "v1.1");
+    t.backend().assert_contents("> v1.1");
+
+    let options = AskOptions::new().placeholder("your name".into());
+    let t = term(vec![]);
+    assert_eq!(t.try_ask_with("> ".into(), &options), Err(Error::InputEnded));
+    t.backend().assert_contents("> your name");
+    t.backend().feed_script("<Left>\n").unwrap();
+    assert_eq!(t.try_ask_with("\n> ".into(), &options), Ok(String::new()));
+    t.backend().assert_contents(">\n>");
+    let t = Terminal::with_backend(AnsiBackend::new(Vec::new(), &b"al\r"[..]));
+    assert_eq!(t.ask_with("> ".into(), &options), "al");
+    assert!(String::from_utf8_lossy(&t.backend().writer()).contains("> \x1b[2myour name\x1b[22m"));
+
+    let options = AskOptions::new().default_answer("main".into()).placeholder("branch".into());
+    let t = Terminal::with_backend(VirtualBackend::new(30, 5));
+    t.backend().feed_script("dev\n\n\n").unwrap();
+    assert_eq!(t.ask_with("> ".into(), &options), "dev");
+    t.backend().assert_contents("> (default: main) dev");
+    assert_eq!(t.ask_with("\n> ".into(), &options), "main");
+    t.backend().assert_contents("> (default: main) dev\n> (default: main) main");
+  }
+
+  #[test]
+  fn text_area() {
+    let t = term(vec![]);
+    t.backend().feed_script("one\ntwo\nthree<Up><Up><Up><End>!<Down><Home><BS>").unwrap();
+    assert_eq!(t.try_text_area("".into(), &TextAreaOptions::new().height(2)), Err(Error::InputEnded));
+    t.backend().assert_contents("one!two\nthree");
+    assert_eq!(t.raw_posxy(), (4, 0));
+
+    let options = TextAreaOptions::new().submit_key(Key::Enter.with_mods(Modifiers::ALT)).initial("a\nb".into());
+    let t = term(vec![]);
+    t.backend().feed(vec![Key::ArrowUp, Key::Delete, Key::Enter, Key::End, Key::Enter.with_mods(Modifiers::ALT)]);
+    assert_eq!(t.text_area("$ ".into(), &options), "a\nb");
+    t.backend().assert_contents("$ a\n  b");
+    assert_eq!(t.raw_posxy(), (3, 1));
+    let t = term(vec![]);
+    t.backend().feed_event(Event::Paste("x\ty\nz".into()));
+    t.backend().feed(vec![Key::Escape]);
+    assert_eq!(t.try_text_area("$ ".into(), &options), Err(Error::Cancelled));
+    t.backend().assert_contents("$ a\n  bxy\n  z");
+
+    // at the bottom of a screen which does not scroll, the viewport only has the rows left
+    let t = Terminal::with_backend(VirtualBackend::new(20, 4));
+    t.out("line1\nline2\n".into());
+    t.backend().feed_script("x\ny\nz<C-d>").unwrap();
+    assert_eq!(t.text_area("msg: ".into(), &TextAreaOptions::new().height(3)), "x\ny\nz");
+    t.backend().assert_contents("line1\nline2\nmsg: y\n     z");
+    let t = Terminal::with_backend(VirtualBackend::new(20, 4));
+    t.out("line1\nline2\nline3\n".into());
+    t.backend().feed_script("x\ny<C-d>").unwrap();
+    assert_eq!(t.text_area("msg: ".into(), &TextAreaOptions::new().height(3)), "x\ny");
+    t.backend().assert_contents("line1\nline2\nline3\nmsg: y");
+  }
+
+  #[test]
+  fn passwords() {
+    let options = PasswordOptions::new().min_len(4).validator(|s| match s.chars().any(|c| c.is_ascii_digit()) {
+      true => Ok(()),
+      false => Err("needs a digit".into())
+    });
+    let ask = |script: &str, options: &PasswordOptions| {
+      let t = Terminal::with_backend(VirtualBackend::new(40, 5));
+      t.backend().feed_script(script).unwrap();
+      let password = t.try_password("> ".into(), options).map(|s| s.expose().to_string());
+      (password, t.backend().contents())
+    };
+    assert_eq!(ask("abc\n", &options), (Err(Error::InputEnded), "> ***\nmust be at least 4 characters".into()));
+    assert_eq!(ask("abcd\n", &options), (Err(Error::InputEnded), "> ****\nneeds a digit".into()));
+    assert_eq!(ask("ab1<C-r>", &options), (Err(Error::InputEnded), "> ab1".into()));
+    assert_eq!(ask("ab<C-r>1<C-r>2\n", &options), (Ok("ab12".into()), "> ****".into()));
+    // submitting while revealed masks the password again
+    assert_eq!(ask("ab<C-r>12\n", &options), (Ok("ab12".into()), "> ****".into()));
+    let mut layer = Layer::new(0, 0);
+    layer.set_content("ab12".into());
+    layer.wipe();
+    assert_eq!(layer.get_content(), "");
+    assert_eq!(ask("ab<C-r>", &PasswordOptions::new().mask('#').reveal_key(None)), (Err(Error::InputEnded), "> ##".into()));
+    let options = options.confirm("again: ".into());
+    assert_eq!(ask("abc1\n<Esc>", &options), (Err(Error::Cancelled), "> ****\nagain:".into()));
+    assert_eq!(ask("abc1\nabc2\n", &options), (Err(Error::InputEnded), "> ****\nagain: ****\ndoes not match".into()));
+
+    let t = term(vec![]);
+    t.backend().feed_script("1234\n1234\n").unwrap();
+    let secret = t.password("> ".into(), &options);
+    assert_eq!(secret.len(), 4);
+    assert!(!format!("{:?}", secret).contains("1234"));
+    assert_eq!(format!("{:?}", secret), "Secret(***)");
+  }
+
+  #[test]
+  fn snapshot_diff() {
+    assert_eq!(snapshot::diff("a", "a"), None);
+    assert_eq!(snapshot::diff("a\nb ", "a\nc\nd").unwrap(), "  |a|\n- |b |\n+ |c|\n+ |d|\n");
   }
 }
@@ -0,0 +1,357 @@
+//! Options for the prompts of a `Terminal`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cli::Key;
+use crate::complete::Completer;
+
+// checks an answer, returning the error to show for a rejected one
+type Validator = dyn Fn(&str) -> Result<(), String>;
+// the answer after ArrowUp (true) or ArrowDown (false), if they change it
+type Stepper = dyn Fn(&str, bool) -> Option<String>;
+
+/// Options for `Terminal::ask_with`.
+///
+/// # Examples
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # use utile_cli::complete::WordCompleter;
+/// # use utile_cli::prompt::AskOptions;
+/// # let t = Terminal::new();
+/// let options = AskOptions::new().completer(WordCompleter::new(vec!["start", "stop", "status"]));
+/// let command = t.ask_with("> ".into(), &options);
+/// ```
+#[derive(Default)]
+pub struct AskOptions {
+  pub(crate) completer: Option<Box<dyn Completer>>,
+  pub(crate) validator: Option<Box<Validator>>,
+  pub(crate) stepper: Option<Box<Stepper>>,
+  pub(crate) initial: String,
+  pub(crate) placeholder: Option<String>,
+  pub(crate) default: Option<String>,
+  pub(crate) reveal: Option<Key>
+}
+
+impl AskOptions {
+  /// Returns the options of a plain `ask`.
+  pub fn new() -> AskOptions {
+    AskOptions::default()
+  }
+
+  /// Sets what Tab completes the line with.
+  ///
+  /// Tab completes the prefix shared by every candidate, and pressing it again lists the candidates below the prompt.
+  pub fn completer<C: Completer + 'static>(mut self, completer: C) -> AskOptions {
+    self.completer = Some(Box::new(completer));
+    self
+  }
+
+  /// Sets the text the answer starts with, which can be edited like anything typed.
+  pub fn initial(mut self, text: String) -> AskOptions {
+    self.initial = text;
+    self
+  }
+
+  /// Sets a hint drawn dimmed in the empty prompt, until the first key is pressed.
+  pub fn placeholder(mut self, hint: String) -> AskOptions {
+    self.placeholder = Some(hint);
+    self
+  }
+
+  /// Sets the answer Enter submits on an empty line, shown like "(default: main)" after the prefix.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::VirtualBackend;
+  /// # use utile_cli::cli::*;
+  /// # use utile_cli::prompt::AskOptions;
+  /// let t = Terminal::with_backend(VirtualBackend::new(30, 5));
+  /// t.backend().feed_script("\n").unwrap();
+  /// let branch = t.ask_with("branch ".into(), &AskOptions::new().default_answer("main".into()));
+  /// assert_eq!(branch, "main");
+  /// assert_eq!(t.backend().contents(), "branch (default: main) main");
+  /// ```
+  pub fn default_answer(mut self, answer: String) -> AskOptions {
+    self.default = Some(answer);
+    self
+  }
+
+  /// Sets a check the answer must pass before Enter submits it.
+  ///
+  /// A rejected answer stays in the prompt, with the error shown on the line below until the answer is edited,
+  /// or after the answer if the prompt is on the last row of a screen that does not scroll.
+  ///
+  /// # Examples
+  /// ```
+  /// # use utile_cli::backend::VirtualBackend;
+  /// # use utile_cli::cli::*;
+  /// # use utile_cli::prompt::AskOptions;
+  /// let t = Terminal::with_backend(VirtualBackend::new(30, 5));
+  /// let options = AskOptions::new().validator(|s| if s.contains('@') { Ok(()) } else { Err("not an email".into()) });
+  /// t.backend().feed_script("me\n").unwrap();
+  /// assert!(t.try_ask_with("email: ".into(), &options).is_err());
+  /// assert_eq!(t.backend().contents(), "email: me\nnot an email");
+  /// ```
+  pub fn validator<F: Fn(&str) -> Result<(), String> + 'static>(mut self, validator: F) -> AskOptions {
+    self.validator = Some(Box::new(validator));
+    self
+  }
+}
+
+/// A number type for `Terminal::ask_number`.
+pub trait Number: Copy + PartialOrd + FromStr + fmt::Display + 'static {
+  /// The value stepping starts from in an empty prompt, unless it is out of bounds.
+  const ZERO: Self;
+  /// The default step.
+  const ONE: Self;
+
+  /// Returns `self + step`, or `None` if it overflows.
+  fn step_up(self, step: Self) -> Option<Self>;
+
+  /// Returns `self - step`, or `None` if it overflows.
+  fn step_down(self, step: Self) -> Option<Self>;
+}
+
+macro_rules! integers {
+  ($($t:ty)*) => {$(
+    impl Number for $t {
+      const ZERO: $t = 0;
+      const ONE: $t = 1;
+
+      fn step_up(self, step: $t) -> Option<$t> {
+        self.checked_add(step)
+      }
+
+      fn step_down(self, step: $t) -> Option<$t> {
+        self.checked_sub(step)
+      }
+    }
+  )*}
+}
+
+macro_rules! floats {
+  ($($t:ty)*) => {$(
+    impl Number for $t {
+      const ZERO: $t = 0.0;
+      const ONE: $t = 1.0;
+
+      fn step_up(self, step: $t) -> Option<$t> {
+        Some(self + step)
+      }
+
+      fn step_down(self, step: $t) -> Option<$t> {
+        Some(self - step)
+      }
+    }
+  )*}
+}
+
+integers!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+floats!(f32 f64);
+
+/// Options for `Terminal::ask_number`.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::backend::VirtualBackend;
+/// # use utile_cli::cli::*;
+/// # use utile_cli::prompt::NumberOptions;
+/// let t = Terminal::with_backend(VirtualBackend::new(20, 5));
+/// t.backend().feed_script("<Up><Up><Up>\n").unwrap();
+/// let port: u16 = t.ask_number("port: ".into(), &NumberOptions::new().min(8000).max(8080).step(40));
+/// assert_eq!(port, 8080);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumberOptions<T> {
+  pub(crate) min: Option<T>,
+  pub(crate) max: Option<T>,
+  pub(crate) step: T
+}
+
+impl<T: Number> NumberOptions<T> {
+  /// Returns options without bounds and a step of one.
+  pub fn new() -> NumberOptions<T> {
+    NumberOptions { min: None, max: None, step: T::ONE }
+  }
+
+  /// Sets the smallest number accepted.
+  pub fn min(mut self, min: T) -> NumberOptions<T> {
+    self.min = Some(min);
+    self
+  }
+
+  /// Sets the largest number accepted.
+  pub fn max(mut self, max: T) -> NumberOptions<T> {
+    self.max = Some(max);
+    self
+  }
+
+  /// Sets how much ArrowUp and ArrowDown change the number by.
+  pub fn step(mut self, step: T) -> NumberOptions<T> {
+    self.step = step;
+    self
+  }
+
+  // the error for a number out of bounds, or one which does not compare such as NaN
+  pub(crate) fn check(&self, n: T) -> Result<T, String> {
+    if n.partial_cmp(&n).is_none() {
+      return Err("not a number".to_string());
+    }
+    match (self.min, self.max) {
+      (Some(min), _) if n < min => Err(format!("must be at least {}", min)),
+      (_, Some(max)) if n > max => Err(format!("must be at most {}", max)),
+      _ => Ok(n)
+    }
+  }
+
+  // zero, or the bound nearest to it if it is out of bounds
+  pub(crate) fn start(&self) -> T {
+    match (self.min, self.max) {
+      (Some(min), _) if T::ZERO < min => min,
+      (_, Some(max)) if T::ZERO > max => max,
+      _ => T::ZERO
+    }
+  }
+
+  // the number after stepping up or down from `text`, kept within bounds
+  pub(crate) fn step_from(&self, text: &str, up: bool) -> Option<T> {
+    let stepped = match text.trim().parse::<T>() {
+      // NaN does not step, so it starts over like an empty answer
+      Ok(n) if n.partial_cmp(&n).is_none() => Some(self.start()),
+      Ok(n) if up => n.step_up(self.step),
+      Ok(n) => n.step_down(self.step),
+      Err(_) => Some(self.start())
+    };
+    let n = match (stepped, self.min, self.max) {
+      (Some(n), Some(min), _) if n < min => min,
+      (Some(n), _, Some(max)) if n > max => max,
+      (Some(n), ..) => n,
+      (None, _, Some(max)) if up => max,
+      (None, Some(min), _) if !up => min,
+      (None, ..) => return None
+    };
+    Some(n)
+  }
+}
+
+impl<T: Number> Default for NumberOptions<T> {
+  fn default() -> NumberOptions<T> {
+    NumberOptions::new()
+  }
+}
+
+/// Options for `Terminal::text_area`.
+///
+/// # Examples
+/// ```no_run
+/// # use utile_cli::cli::*;
+/// # use utile_cli::prompt::TextAreaOptions;
+/// # let t = Terminal::new();
+/// let options = TextAreaOptions::new().height(10).submit_key(Key::Enter.with_mods(Modifiers::ALT));
+/// let message = t.text_area("message: ".into(), &options);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextAreaOptions {
+  pub(crate) height: usize,
+  pub(crate) submit: Key,
+  pub(crate) initial: String
+}
+
+impl TextAreaOptions {
+  /// Returns options for an empty text area of 5 lines, submitted with Ctrl-D.
+  pub fn new() -> TextAreaOptions {
+    TextAreaOptions { height: 5, submit: Key::ctrl('d'), initial: String::new() }
+  }
+
+  /// Sets the number of lines shown, the text scrolling within them, at least one.
+  pub fn height(mut self, height: usize) -> TextAreaOptions {
+    self.height = height.max(1);
+    self
+  }
+
+  /// Sets the key which submits the text, as Enter starts a new line.
+  pub fn submit_key(mut self, key: Key) -> TextAreaOptions {
+    self.submit = key;
+    self
+  }
+
+  /// Sets the text the text area starts with, which can be edited like anything typed.
+  pub fn initial(mut self, text: String) -> TextAreaOptions {
+    self.initial = text;
+    self
+  }
+}
+
+impl Default for TextAreaOptions {
+  fn default() -> TextAreaOptions {
+    TextAreaOptions::new()
+  }
+}
+
+/// Options for `Terminal::password`.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::backend::VirtualBackend;
+/// # use utile_cli::cli::*;
+/// # use utile_cli::prompt::PasswordOptions;
+/// let t = Terminal::with_backend(VirtualBackend::new(30, 5));
+/// t.backend().feed_script("hunter2\nhunter3\n<C-u>hunter2\n").unwrap();
+/// let options = PasswordOptions::new().min_len(6).confirm("again: ".into());
+/// let password = t.password("password: ".into(), &options);
+/// assert_eq!(password.expose(), "hunter2");
+/// assert_eq!(t.backend().contents(), "password: *******\nagain: *******");
+/// ```
+pub struct PasswordOptions {
+  pub(crate) mask: char,
+  pub(crate) reveal: Option<Key>,
+  pub(crate) confirm: Option<String>,
+  pub(crate) min_len: usize,
+  pub(crate) validator: Option<Box<Validator>>
+}
+
+impl PasswordOptions {
+  /// Returns options for a password masked with '*', revealed with Ctrl-R, without a confirmation or a minimum length.
+  pub fn new() -> PasswordOptions {
+    PasswordOptions { mask: '*', reveal: Some(Key::ctrl('r')), confirm: None, min_len: 0, validator: None }
+  }
+
+  /// Sets the character shown for every character of the password.
+  pub fn mask(mut self, mask: char) -> PasswordOptions {
+    self.mask = mask;
+    self
+  }
+
+  /// Sets the key which switches between showing the password and masking it again, or `None` to always mask it.
+  pub fn reveal_key(mut self, key: Option<Key>) -> PasswordOptions {
+    self.reveal = key;
+    self
+  }
+
+  /// Asks for the password a second time on the next line, prefixed with `prefix`.
+  ///
+  /// A second entry which does not match stays in the prompt with an error below it, to be corrected.
+  pub fn confirm(mut self, prefix: String) -> PasswordOptions {
+    self.confirm = Some(prefix);
+    self
+  }
+
+  /// Sets the fewest characters the password may have.
+  pub fn min_len(mut self, len: usize) -> PasswordOptions {
+    self.min_len = len;
+    self
+  }
+
+  /// Sets a check such as a strength test the password must pass, like `AskOptions::validator`.
+  pub fn validator<F: Fn(&str) -> Result<(), String> + 'static>(mut self, validator: F) -> PasswordOptions {
+    self.validator = Some(Box::new(validator));
+    self
+  }
+}
+
+impl Default for PasswordOptions {
+  fn default() -> PasswordOptions {
+    PasswordOptions::new()
+  }
+}
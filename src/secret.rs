@@ -0,0 +1,55 @@
+//! Answers which are wiped from memory once dropped, as returned by `Terminal::password`.
+
+use std::fmt;
+
+use zeroize::Zeroizing;
+
+/// A string which is overwritten with zeros when dropped, so a password does not linger in memory after use.
+///
+/// It is not printed by `Debug`, and its contents have to be asked for with `expose`.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::secret::Secret;
+/// let secret = Secret::new("hunter2".to_string());
+/// assert_eq!(secret.expose(), "hunter2");
+/// assert_eq!(format!("{:?}", secret), "Secret(***)");
+/// ```
+#[derive(Clone, Default)]
+pub struct Secret {
+  inner: Zeroizing<String>
+}
+
+impl Secret {
+  /// Returns a secret taking ownership of `s`, without copying it.
+  pub fn new(s: String) -> Secret {
+    Secret { inner: Zeroizing::new(s) }
+  }
+
+  /// Returns the contents of the secret.
+  pub fn expose(&self) -> &str {
+    &self.inner
+  }
+
+  /// Returns the length of the secret in bytes.
+  pub fn len(&self) -> usize {
+    self.inner.len()
+  }
+
+  /// Returns true if the secret is empty.
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+}
+
+impl From<String> for Secret {
+  fn from(s: String) -> Secret {
+    Secret::new(s)
+  }
+}
+
+impl fmt::Debug for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Secret(***)")
+  }
+}
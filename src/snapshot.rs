@@ -0,0 +1,78 @@
+//! Helpers for comparing rendered screens against expected text.
+//!
+//! These are mostly used with `VirtualBackend` in tests, for example:
+//! ```no_run
+//! # use utile_cli::backend::VirtualBackend;
+//! # use utile_cli::cli::*;
+//! let t = Terminal::with_backend(VirtualBackend::new(20, 5));
+//! t.outln("Hello world!".into());
+//! t.backend().assert_snapshot("snapshots/hello.txt");
+//! ```
+//!
+//! Golden files can be (re)written by running the tests with the `UPDATE_SNAPSHOTS` environment variable set.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Compares `actual` against the contents of the golden file at `path`, panicking with a diff if they differ.
+///
+/// Trailing newlines in the golden file are ignored.
+/// If `UPDATE_SNAPSHOTS` is set, the golden file is written with `actual` instead.
+pub fn assert_snapshot<P: AsRef<Path>>(actual: &str, path: P) {
+  let path = path.as_ref();
+  if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir).expect("Could not create the snapshot directory!");
+    }
+    fs::write(path, format!("{}\n", actual)).expect("Could not write the snapshot!");
+    return;
+  }
+  let expected = match fs::read_to_string(path) {
+    Ok(s) => s,
+    Err(e) => panic!("Could not read snapshot {}: {} (run with UPDATE_SNAPSHOTS=1 to create it)", path.display(), e)
+  };
+  if let Some(d) = diff(expected.trim_end_matches('\n'), actual) {
+    panic!("Screen does not match snapshot {}:\n{}", path.display(), d);
+  }
+}
+
+/// Compares `actual` against `expected`, panicking with a diff if they differ.
+pub fn assert_text(expected: &str, actual: &str) {
+  if let Some(d) = diff(expected, actual) {
+    panic!("Screen does not match:\n{}", d);
+  }
+}
+
+/// Returns a line by line diff of two texts, or `None` if they are equal.
+///
+/// Matching lines are prefixed with `  `, expected lines with `- ` and actual lines with `+ `.
+/// Lines are shown between `|` characters so that trailing whitespace is visible.
+///
+/// # Examples
+/// ```
+/// # use utile_cli::snapshot::diff;
+/// assert_eq!(diff("a\nb", "a\nc").unwrap(), "  |a|\n- |b|\n+ |c|\n");
+/// ```
+pub fn diff(expected: &str, actual: &str) -> Option<String> {
+  if expected == actual {
+    return None;
+  }
+  let expected: Vec<&str> = expected.split('\n').collect();
+  let actual: Vec<&str> = actual.split('\n').collect();
+  let mut r = String::new();
+  for i in 0..expected.len().max(actual.len()) {
+    match (expected.get(i), actual.get(i)) {
+      (Some(e), Some(a)) if e == a => r.push_str(&format!("  |{}|\n", e)),
+      (e, a) => {
+        if let Some(e) = e {
+          r.push_str(&format!("- |{}|\n", e));
+        }
+        if let Some(a) = a {
+          r.push_str(&format!("+ |{}|\n", a));
+        }
+      }
+    }
+  }
+  Some(r)
+}
@@ -0,0 +1,15 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Returns the number of terminal cells `s` covers.
+pub fn width(s: &str) -> usize {
+  graphemes(s).map(|(_, w)| w).sum()
+}
+
+/// Returns the grapheme clusters of `s` along with the number of cells each covers.
+///
+/// Clusters are at most two cells wide, as terminals draw emoji sequences in one double width cell.
+/// Combining marks are part of their cluster, so only clusters made up of control characters cover no cells.
+pub fn graphemes(s: &str) -> impl Iterator<Item = (&str, usize)> {
+  s.graphemes(true).map(|g| (g, g.width().min(2)))
+}